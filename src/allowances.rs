@@ -4,8 +4,15 @@ use cosmwasm_std::{
 };
 use cw20::{AllowanceResponse, Cw20ReceiveMsg};
 
+use crate::contract::prepare_balance_hooks;
 use crate::error::ContractError;
-use crate::state::{ALLOWANCES, BALANCES, TOKEN_INFO};
+use crate::freeze::assert_can_transfer;
+use crate::membership::assert_members;
+use crate::hook::BalanceDiff;
+use crate::vesting::debit_balance;
+use crate::ratelimit::consume_outflow;
+use crate::stats::{record_balance_changes, record_transfer, settle_tiered_tax};
+use crate::state::{ALLOWANCES, STATE, TOKEN_INFO, balances};
 
 
 // this can be used to update a lower allowance - call bucket.update with proper keys
@@ -45,25 +52,27 @@ pub fn execute_burn_from(
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     let owner_addr = deps.api.addr_validate(&owner)?;
+    assert_can_transfer(deps.storage, &[&owner_addr, &info.sender])?;
 
     // deduct allowance before doing anything else have enough allowance
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
 
+    let settled = settle_tiered_tax(deps.storage, &env.block, &[&owner_addr])?;
+
     // lower balance
-    BALANCES.update(
-        deps.storage,
-        &owner_addr,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
+    let owner_balance = debit_balance(deps.storage, &env.block, &owner_addr, amount)?;
+    consume_outflow(deps.storage, &env.block, &owner_addr, amount)?;
     // reduce total_supply
     TOKEN_INFO.update(deps.storage, |mut meta| -> StdResult<_> {
         meta.total_supply = meta.total_supply.checked_sub(amount)?;
         Ok(meta)
     })?;
 
-    let res = Response::new().add_attributes(vec![
+    let diffs = vec![BalanceDiff::debited(&owner_addr, owner_balance, amount)];
+    record_balance_changes(deps.storage, &env.block, &diffs)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
+
+    let res = Response::new().add_submessages(hooks).add_attributes(vec![
         attr("action", "burn_from"),
         attr("from", owner),
         attr("by", info.sender),
@@ -83,23 +92,24 @@ pub fn execute_send_from(
 ) -> Result<Response, ContractError> {
     let rcpt_addr = deps.api.addr_validate(&contract)?;
     let owner_addr = deps.api.addr_validate(&owner)?;
+    assert_can_transfer(deps.storage, &[&owner_addr, &info.sender, &rcpt_addr])?;
+    assert_members(deps.as_ref(), &[&owner_addr, &rcpt_addr])?;
 
     // deduct allowance before doing anything else have enough allowance
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
 
+    let settled = settle_tiered_tax(deps.storage, &env.block, &[&owner_addr, &rcpt_addr])?;
+
     // move the tokens to the contract
-    BALANCES.update(
-        deps.storage,
-        &owner_addr,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
-    BALANCES.update(
+    let owner_balance = debit_balance(deps.storage, &env.block, &owner_addr, amount)?;
+    let rcpt_balance = balances().update(
         deps.storage,
         &rcpt_addr,
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
+    let period = STATE.load(deps.storage)?.current_period;
+    consume_outflow(deps.storage, &env.block, &owner_addr, amount)?;
+    record_transfer(deps.storage, period, &owner_addr, &rcpt_addr, amount)?;
 
     let attrs = vec![
         attr("action", "send_from"),
@@ -117,7 +127,17 @@ pub fn execute_send_from(
     }
     .into_cosmos_msg(contract)?;
 
-    let res = Response::new().add_message(msg).add_attributes(attrs);
+    let diffs = vec![
+        BalanceDiff::debited(&owner_addr, owner_balance, amount),
+        BalanceDiff::credited(&rcpt_addr, rcpt_balance, amount),
+    ];
+    record_balance_changes(deps.storage, &env.block, &diffs)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
+
+    let res = Response::new()
+        .add_message(msg)
+        .add_submessages(hooks)
+        .add_attributes(attrs);
     Ok(res)
 }
 
@@ -0,0 +1,85 @@
+use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult};
+use cw_storage_plus::Bound;
+
+use crate::contract::RESOLUTION_FACTOR;
+use crate::enumerable::page_limit;
+use crate::error::ContractError;
+use crate::query::AccountsByClassResponse;
+use crate::state::{account_classes, AccountClass, ClassConfig, ADMIN, CLASSES};
+
+/// Create a class or change its tax level. Must be called by Admin
+pub fn execute_update_class(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    class: String,
+    tax_level: u128,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    if tax_level > RESOLUTION_FACTOR {
+        return Err(ContractError::InvalidTaxLevel {});
+    }
+
+    let config = ClassConfig {
+        tax_level,
+        since: env.block.time,
+    };
+    CLASSES.save(deps.storage, &class, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_class")
+        .add_attribute("class", class)
+        .add_attribute("tax_level", tax_level.to_string()))
+}
+
+/// Put an account into a class, or back to the general rate with None. Must be called by Admin
+pub fn execute_assign_class(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    class: Option<String>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
+
+    match &class {
+        Some(class) => {
+            if !CLASSES.has(deps.storage, class) {
+                return Err(ContractError::ClassNotFound {});
+            }
+            let assigned = AccountClass {
+                class: class.clone(),
+                since: env.block.time,
+            };
+            account_classes().save(deps.storage, &addr, &assigned)?;
+        }
+        None => account_classes().remove(deps.storage, &addr)?,
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "assign_class")
+        .add_attribute("address", address)
+        .add_attribute("class", class.unwrap_or_else(|| "None".to_string())))
+}
+
+pub fn query_accounts_by_class(
+    deps: Deps,
+    class: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AccountsByClassResponse> {
+    let limit = page_limit(limit);
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let accounts = account_classes()
+        .idx
+        .class
+        .prefix(class)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(Into::into))
+        .collect::<StdResult<_>>()?;
+
+    Ok(AccountsByClassResponse { accounts })
+}
@@ -1,7 +1,7 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128, Timestamp, attr, Addr, BlockInfo, Storage
+    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128, Timestamp, attr, Addr, BlockInfo, Storage, SubMsg
 };
 
 use cw2::set_contract_version;
@@ -13,10 +13,57 @@ use crate::allowances::{
     execute_burn_from, execute_send_from,
     query_allowance,
 };
-use crate::enumerable::{query_all_accounts, query_all_allowances};
+use crate::enumerable::{
+    query_all_accounts, query_all_allowances, query_all_balances, query_frozen_accounts,
+    query_minters, query_top_holders,
+};
+use cw_utils::maybe_addr;
+use crate::classes::{execute_assign_class, execute_update_class, query_accounts_by_class};
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{MinterData, TokenInfo, BALANCES, TOKEN_INFO, State, STATE, ALLOWANCES};
+use crate::hook::{BalanceChangedHookMsg, BalanceDiff};
+use crate::msg::{ExecuteMsg, InitialVesting, InstantiateMsg, QueryMsg};
+use crate::state::{MinterConfig, MinterData, ReserveConfig, TokenInfo, TOKEN_INFO, State, STATE, ALLOWANCES, ADMIN, GUARDIAN, HOOKS, MINTERS, RESERVE, RESERVE_CONFIG, TWAB, TwabPoint, ACCOUNT_STATS, AccountStats, balances};
+use crate::freeze::{
+    assert_can_transfer, execute_freeze, execute_pause, execute_update_guardian, query_paused,
+};
+use crate::membership::{assert_members, execute_set_membership_group, query_membership_group};
+use crate::ownership::{
+    execute_accept_admin, execute_accept_minter, execute_cancel_admin_proposal,
+    execute_cancel_minter_proposal, execute_propose_admin, execute_propose_minter,
+    execute_renounce_admin, query_pending_ownership,
+};
+use crate::permit::{execute_transfer_with_permit, query_nonce};
+use crate::schedule::{
+    execute_cancel_scheduled, execute_schedule_transfer, execute_scheduled, query_scheduled,
+};
+use crate::simulate::query_simulate;
+use crate::stats::{
+    query_account_stats, query_period_metrics, record_balance_changes, record_transfer,
+    roll_over_metrics, settle_tiered_tax,
+};
+use crate::subscriptions::{
+    execute_cancel_subscription, execute_create_subscription, execute_process_subscriptions,
+    query_subscriptions_by_payee, query_subscriptions_by_payer,
+};
+use crate::sweep::execute_sweep_dust;
+use crate::tiers::{
+    execute_set_exempt_floor, execute_update_tax_tiers, query_tax_tiers, query_taxable_balance,
+};
+use crate::projection::{query_projected_balance, query_tax_due};
+use crate::twab::query_twab_balance;
+use crate::ubi::{execute_distribute, execute_set_ubi_group, query_ubi_status, reserve_ubi};
+use crate::vesting::{add_vesting, debit_balance, execute_mint_vesting, new_vesting, query_vesting};
+use crate::ratelimit::{
+    consume_outflow, execute_set_global_rate_limit, execute_set_rate_limit, query_rate_limit,
+};
+use crate::rebates::{
+    execute_claim_rebate, execute_set_rebate_share, query_pending_rebate, reserve_rebates,
+};
+use crate::reserve::{execute_deposit, execute_redeem, query_reserve};
+use crate::savings::{
+    execute_claim_unlocked, execute_lock, execute_unlock, execute_update_savings_config,
+    query_locks, query_savings_config,
+};
 use crate::query::{DemurrageAmountResponse, SinkAddressResponse, TaxLevelResponse};
 
 // version info for migration info
@@ -28,7 +75,7 @@ const GROWTH_RESOLUTION_FACTOR: u128 = 100000000000; // 10^11
 
 
 //10^39
-const RESOLUTION_FACTOR: u128 = NANO_DIVDER * GROWTH_RESOLUTION_FACTOR; //this value may get out of bound
+pub const RESOLUTION_FACTOR: u128 = NANO_DIVDER * GROWTH_RESOLUTION_FACTOR; //this value may get out of bound
 
 
 /*
@@ -55,16 +102,17 @@ pub fn instantiate(
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     // check valid token info
     msg.validate()?;
+    //demurrageAmount = 100000000000000000000000000000000000000 - _taxLevelMinute; // Represents 38 decimal places, same as resolutionFactor
+    //demurrageAmount = 100000000000000000000000000000000000000;
+    let demurrage_amount: u128 = 100000000000000000000000; //10^23
     // create initial accounts
-    let total_supply = create_accounts(&mut deps, &msg.initial_balances)?;
+    let total_supply = create_accounts(&mut deps, &_env.block, demurrage_amount, &msg.initial_balances)?;
+    create_initial_vesting(&mut deps, &_env.block, &msg.initial_balances, &msg.initial_vesting)?;
 
 
     // Demurrage Setup 
     let period_start = _env.block.time;
     let period_duration = msg.period_minutes * 60;
-    //demurrageAmount = 100000000000000000000000000000000000000 - _taxLevelMinute; // Represents 38 decimal places, same as resolutionFactor
-    //demurrageAmount = 100000000000000000000000000000000000000;
-    let demurrage_amount: u128 = 100000000000000000000000; //10^23
     //demurragePeriod = 1;
     let tax_level = msg.tax_level_minute;
     let base_ten: u32 = 10;
@@ -109,11 +157,27 @@ pub fn instantiate(
     };
     TOKEN_INFO.save(deps.storage, &data)?;
 
+    let admin = maybe_addr(deps.api, msg.admin)?;
+    ADMIN.set(deps.branch(), admin)?;
+    GUARDIAN.set(deps.branch(), None)?;
+
+    if let Some(reserve) = msg.reserve {
+        let config = ReserveConfig {
+            denom: reserve.denom,
+            ratio: reserve.ratio,
+            redemption_fee: reserve.redemption_fee,
+        };
+        RESERVE_CONFIG.save(deps.storage, &config)?;
+        RESERVE.save(deps.storage, &Uint128::zero())?;
+    }
+
     Ok(Response::default())
 }
 
 pub fn create_accounts(
     deps: &mut DepsMut,
+    block: &BlockInfo,
+    demurrage_amount: u128,
     accounts: &[Cw20Coin],
 ) -> Result<Uint128, ContractError> {
     validate_accounts(accounts)?;
@@ -121,13 +185,45 @@ pub fn create_accounts(
     let mut total_supply = Uint128::zero();
     for row in accounts {
         let address = deps.api.addr_validate(&row.address)?;
-        BALANCES.save(deps.storage, &address, &row.amount)?;
+        balances().save(deps.storage, &address, &row.amount)?;
+        // the modifier starts out neutral, so the worth is the amount
+        let point = TwabPoint {
+            cumulative: Uint128::zero(),
+            balance: row.amount,
+            demurrage_amount,
+        };
+        TWAB.save(deps.storage, (&address, block.time.seconds()), &point)?;
+        let stats = AccountStats {
+            demurrage_amount,
+            updated: block.time,
+            ..AccountStats::default()
+        };
+        ACCOUNT_STATS.save(deps.storage, &address, &stats)?;
         total_supply += row.amount;
     }
 
     Ok(total_supply)
 }
 
+/// Lock the initial balance of every account with a vesting schedule
+pub fn create_initial_vesting(
+    deps: &mut DepsMut,
+    block: &BlockInfo,
+    accounts: &[Cw20Coin],
+    vesting: &[InitialVesting],
+) -> Result<(), ContractError> {
+    for row in vesting {
+        let amount = accounts
+            .iter()
+            .find(|c| c.address == row.address)
+            .map(|c| c.amount)
+            .ok_or(ContractError::InvalidVesting {})?;
+        let address = deps.api.addr_validate(&row.address)?;
+        add_vesting(deps.storage, &address, new_vesting(block, amount, row.schedule.clone())?)?;
+    }
+    Ok(())
+}
+
 pub fn validate_accounts(accounts: &[Cw20Coin]) -> Result<(), ContractError> {
     let mut addresses = accounts.iter().map(|c| &c.address).collect::<Vec<_>>();
     addresses.sort();
@@ -164,6 +260,11 @@ pub fn execute(
         ExecuteMsg::Transfer { recipient, amount } => {
             execute_transfer(deps, env, info, recipient, amount)
         }
+        ExecuteMsg::TransferWithPermit {
+            permit,
+            signature,
+            pubkey,
+        } => execute_transfer_with_permit(deps, env, info, permit, signature, pubkey),
         ExecuteMsg::Burn { amount } => execute_burn(deps, env, info, amount),
         ExecuteMsg::Send {
             contract,
@@ -171,6 +272,10 @@ pub fn execute(
             msg,
         } => execute_send(deps, env, info, contract, amount, msg),
         ExecuteMsg::Mint { recipient, amount } => execute_mint(deps, env, info, recipient, amount),
+        ExecuteMsg::TransferBatch { transfers } => {
+            execute_transfer_batch(deps, env, info, transfers)
+        }
+        ExecuteMsg::MintBatch { mints } => execute_mint_batch(deps, env, info, mints),
         ExecuteMsg::IncreaseAllowance {
             spender,
             amount,
@@ -193,15 +298,104 @@ pub fn execute(
             amount,
             msg,
         } => execute_send_from(deps, env, info, owner, contract, amount, msg),
+        ExecuteMsg::MintVesting {
+            recipient,
+            amount,
+            schedule,
+        } => execute_mint_vesting(deps, env, info, recipient, amount, schedule),
         ExecuteMsg::UpdateMinter { new_minter } => {
-            execute_update_minter(deps, env, info, new_minter)
+            execute_propose_minter(deps, env, info, new_minter, None)
         },
+        ExecuteMsg::ProposeMinter { new_minter, expires } => {
+            execute_propose_minter(deps, env, info, new_minter, expires)
+        }
+        ExecuteMsg::AcceptMinter {} => execute_accept_minter(deps, env, info),
+        ExecuteMsg::CancelMinterProposal {} => execute_cancel_minter_proposal(deps, info),
         ExecuteMsg::ChangeSinkAddress{ address } => {
             execute_change_sink_address(deps, address)
         }
         ExecuteMsg::ChangeTaxLevel{ amount } => {
             execute_change_tax_level(deps, amount)
         }
+        ExecuteMsg::Deposit {} => execute_deposit(deps, env, info),
+        ExecuteMsg::Redeem { amount } => execute_redeem(deps, env, info, amount),
+        ExecuteMsg::AddMinter {
+            minter,
+            cap,
+            per_period_limit,
+        } => execute_add_minter(deps, info, minter, cap, per_period_limit),
+        ExecuteMsg::RemoveMinter { minter } => execute_remove_minter(deps, info, minter),
+        ExecuteMsg::UpdateSavingsConfig {
+            tax_level,
+            unbonding_period,
+        } => execute_update_savings_config(deps, info, tax_level, unbonding_period),
+        ExecuteMsg::Lock { amount, duration } => execute_lock(deps, env, info, amount, duration),
+        ExecuteMsg::Unlock {} => execute_unlock(deps, env, info),
+        ExecuteMsg::ClaimUnlocked {} => execute_claim_unlocked(deps, env, info),
+        ExecuteMsg::UpdateClass { class, tax_level } => {
+            execute_update_class(deps, env, info, class, tax_level)
+        }
+        ExecuteMsg::AssignClass { address, class } => {
+            execute_assign_class(deps, env, info, address, class)
+        }
+        ExecuteMsg::SetExemptFloor { amount } => execute_set_exempt_floor(deps, env, info, amount),
+        ExecuteMsg::UpdateTaxTiers { tiers } => execute_update_tax_tiers(deps, env, info, tiers),
+        ExecuteMsg::SetRebateShare { share } => execute_set_rebate_share(deps, info, share),
+        ExecuteMsg::ClaimRebate { period } => execute_claim_rebate(deps, env, info, period),
+        ExecuteMsg::ScheduleTransfer { recipient, amount, at } => {
+            execute_schedule_transfer(deps, env, info, recipient, amount, at)
+        }
+        ExecuteMsg::ExecuteScheduled { limit } => execute_scheduled(deps, env, limit),
+        ExecuteMsg::CancelScheduled { id } => execute_cancel_scheduled(deps, env, info, id),
+        ExecuteMsg::CreateSubscription {
+            payee,
+            amount,
+            interval,
+            max_payments,
+        } => execute_create_subscription(deps, env, info, payee, amount, interval, max_payments),
+        ExecuteMsg::CancelSubscription { id } => execute_cancel_subscription(deps, info, id),
+        ExecuteMsg::ProcessSubscriptions { limit } => {
+            execute_process_subscriptions(deps, env, limit)
+        }
+        ExecuteMsg::SetRateLimit { address, limit } => {
+            execute_set_rate_limit(deps, info, address, limit)
+        }
+        ExecuteMsg::SetGlobalRateLimit { limit } => {
+            execute_set_global_rate_limit(deps, info, limit)
+        }
+        ExecuteMsg::SetMembershipGroup { group } => {
+            execute_set_membership_group(deps, info, group)
+        }
+        ExecuteMsg::SetUbiGroup { group } => execute_set_ubi_group(deps, env, info, group),
+        ExecuteMsg::Distribute { limit } => execute_distribute(deps, env, limit),
+        ExecuteMsg::SweepDust { start_after, limit } => {
+            execute_sweep_dust(deps, env, start_after, limit)
+        }
+        ExecuteMsg::Freeze { address } => execute_freeze(deps, info, address, true),
+        ExecuteMsg::Unfreeze { address } => execute_freeze(deps, info, address, false),
+        ExecuteMsg::Pause {} => execute_pause(deps, info, true),
+        ExecuteMsg::Unpause {} => execute_pause(deps, info, false),
+        ExecuteMsg::UpdateGuardian { guardian } => {
+            execute_update_guardian(deps, info, guardian)
+        }
+        ExecuteMsg::UpdateAdmin { admin } => match admin {
+            // handing over needs the new admin to accept
+            Some(admin) => execute_propose_admin(deps, env, info, admin, None),
+            None => execute_renounce_admin(deps, info),
+        },
+        ExecuteMsg::ProposeAdmin { new_admin, expires } => {
+            execute_propose_admin(deps, env, info, new_admin, expires)
+        }
+        ExecuteMsg::AcceptAdmin {} => execute_accept_admin(deps, env, info),
+        ExecuteMsg::CancelAdminProposal {} => execute_cancel_admin_proposal(deps, info),
+        ExecuteMsg::AddHook { addr } => {
+            let addr = deps.api.addr_validate(&addr)?;
+            Ok(HOOKS.execute_add_hook(&ADMIN, deps, info, addr)?)
+        }
+        ExecuteMsg::RemoveHook { addr } => {
+            let addr = deps.api.addr_validate(&addr)?;
+            Ok(HOOKS.execute_remove_hook(&ADMIN, deps, info, addr)?)
+        }
     }
 }
 
@@ -253,31 +447,23 @@ pub fn execute_transfer(
     .may_load(deps.storage)?
     .ok_or(ContractError::Unauthorized {})?;
 
-    change_period(&mut deps, _env, &mut state);
-
-   let base_value: u128; 
-    base_value = to_base_amount(amount.u128(), state.demurrage_amount);
+    let period_res = change_period(&mut deps, _env.clone(), &mut state)?;
 
     if amount == Uint128::zero() {
         return Err(ContractError::InvalidZeroAmount {});
     }
 
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
+    assert_can_transfer(deps.storage, &[&info.sender, &rcpt_addr])?;
+    assert_members(deps.as_ref(), &[&info.sender, &rcpt_addr])?;
 
-    BALANCES.update(
-        deps.storage,
-        &info.sender,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
-    BALANCES.update(
-        deps.storage,
-        &rcpt_addr,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + Uint128::from(base_value)) },
-    )?;
+    let mut diffs = settle_tiered_tax(deps.storage, &_env.block, &[&info.sender, &rcpt_addr])?;
+    diffs.extend(transfer_tokens(deps.storage, &_env.block, &state, &info.sender, &rcpt_addr, amount)?);
+    let hooks = prepare_balance_hooks(deps.storage, diffs)?;
 
     let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
         .add_attribute("action", "transfer")
         .add_attribute("from", info.sender)
         .add_attribute("to", recipient)
@@ -285,6 +471,96 @@ pub fn execute_transfer(
     Ok(res)
 }
 
+pub fn execute_transfer_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfers: Vec<Cw20Coin>,
+) -> Result<Response, ContractError> {
+    // validate every entry before moving any tokens
+    let recipients = validate_batch(deps.as_ref(), &transfers)?;
+    let mut accounts = vec![&info.sender];
+    accounts.extend(recipients.iter());
+    assert_can_transfer(deps.storage, &accounts)?;
+    assert_members(deps.as_ref(), &accounts)?;
+    let total = transfers.iter().map(|t| t.amount).sum::<Uint128>();
+
+    let mut state = STATE
+    .may_load(deps.storage)?
+    .ok_or(ContractError::Unauthorized {})?;
+
+    // the period is rolled once for the whole batch
+    let period_res = change_period(&mut deps, env.clone(), &mut state)?;
+
+    let mut diffs = settle_tiered_tax(deps.storage, &env.block, &accounts)?;
+    for (rcpt_addr, transfer) in recipients.iter().zip(&transfers) {
+        diffs.extend(transfer_tokens(
+            deps.storage,
+            &env.block,
+            &state,
+            &info.sender,
+            rcpt_addr,
+            transfer.amount,
+        )?);
+    }
+    let hooks = prepare_balance_hooks(deps.storage, diffs)?;
+
+    let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_attribute("action", "transfer_batch")
+        .add_attribute("from", info.sender)
+        .add_attribute("recipients", transfers.len().to_string())
+        .add_attribute("amount", total);
+    Ok(res)
+}
+
+/// Validate the recipients of a batch, none of the amounts may be zero
+fn validate_batch(deps: Deps, batch: &[Cw20Coin]) -> Result<Vec<Addr>, ContractError> {
+    if batch.is_empty() {
+        return Err(ContractError::EmptyBatch {});
+    }
+    batch
+        .iter()
+        .map(|coin| {
+            if coin.amount == Uint128::zero() {
+                return Err(ContractError::InvalidZeroAmount {});
+            }
+            Ok(deps.api.addr_validate(&coin.address)?)
+        })
+        .collect()
+}
+
+/// Move amount from one account to another. The recipient is credited the base
+/// amount of the current demurrage modifier, so change_period and the settlement of
+/// tiered tax must run first. Tokens still locked by a vesting schedule cannot be moved.
+pub fn transfer_tokens(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    state: &State,
+    from: &Addr,
+    to: &Addr,
+    amount: Uint128,
+) -> Result<Vec<BalanceDiff>, ContractError> {
+    let base_value = Uint128::from(to_base_amount(amount.u128(), state.demurrage_amount));
+
+    let from_balance = debit_balance(storage, block, from, amount)?;
+    let to_balance = balances().update(
+        storage,
+        to,
+        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + base_value) },
+    )?;
+    consume_outflow(storage, block, from, amount)?;
+    record_transfer(storage, state.current_period, from, to, amount)?;
+
+    let diffs = vec![
+        BalanceDiff::debited(from, from_balance, amount),
+        BalanceDiff::credited(to, to_balance, base_value),
+    ];
+    record_balance_changes(storage, block, &diffs)?;
+    Ok(diffs)
+}
+
 pub fn execute_transfer_from(
     mut deps: DepsMut,
     env: Env,
@@ -295,6 +571,8 @@ pub fn execute_transfer_from(
 ) -> Result<Response, ContractError> {
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
     let owner_addr = deps.api.addr_validate(&owner)?;
+    assert_can_transfer(deps.storage, &[&owner_addr, &info.sender, &rcpt_addr])?;
+    assert_members(deps.as_ref(), &[&owner_addr, &rcpt_addr])?;
 
     // deduct allowance before doing anything else have enough allowance
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
@@ -303,27 +581,15 @@ pub fn execute_transfer_from(
     .may_load(deps.storage)?
     .ok_or(ContractError::Unauthorized {})?;
 
-    let base_value: u128;
-
-    change_period(&mut deps, env,  &mut state);
-
-    base_value = to_base_amount(amount.u128(), state.demurrage_amount);
-
+    let period_res = change_period(&mut deps, env.clone(),  &mut state)?;
 
-    BALANCES.update(
-        deps.storage,
-        &owner_addr,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
-    BALANCES.update(
-        deps.storage,
-        &rcpt_addr,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + Uint128::from(base_value)) },
-    )?;
+    let mut diffs = settle_tiered_tax(deps.storage, &env.block, &[&owner_addr, &rcpt_addr])?;
+    diffs.extend(transfer_tokens(deps.storage, &env.block, &state, &owner_addr, &rcpt_addr, amount)?);
+    let hooks = prepare_balance_hooks(deps.storage, diffs)?;
 
     let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
         .add_attribute("action", "transfer")
         .add_attribute("from", owner)
         .add_attribute("to", recipient)
@@ -340,22 +606,24 @@ pub fn execute_burn(
     if amount == Uint128::zero() {
         return Err(ContractError::InvalidZeroAmount {});
     }
+    assert_can_transfer(deps.storage, &[&info.sender])?;
+    let settled = settle_tiered_tax(deps.storage, &_env.block, &[&info.sender])?;
 
     // lower balance
-    BALANCES.update(
-        deps.storage,
-        &info.sender,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
+    let balance = debit_balance(deps.storage, &_env.block, &info.sender, amount)?;
+    consume_outflow(deps.storage, &_env.block, &info.sender, amount)?;
     // reduce total_supply
     TOKEN_INFO.update(deps.storage, |mut info| -> StdResult<_> {
         info.total_supply = info.total_supply.checked_sub(amount)?;
         Ok(info)
     })?;
 
+    let diffs = vec![BalanceDiff::debited(&info.sender, balance, amount)];
+    record_balance_changes(deps.storage, &_env.block, &diffs)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
+
     let res = Response::new()
+        .add_submessages(hooks)
         .add_attribute("action", "burn")
         .add_attribute("from", info.sender)
         .add_attribute("amount", amount);
@@ -374,6 +642,63 @@ pub fn execute_mint(
         return Err(ContractError::InvalidZeroAmount {});
     }
 
+    let rcpt_addr = deps.api.addr_validate(&recipient)?;
+    assert_can_transfer(deps.storage, &[&rcpt_addr])?;
+    assert_members(deps.as_ref(), &[&rcpt_addr])?;
+
+    let (state, period_res) = issue_supply(&mut deps, _env.clone(), &info.sender, amount)?;
+
+    // add amount to recipient balance
+    let mut diffs = settle_tiered_tax(deps.storage, &_env.block, &[&rcpt_addr])?;
+    diffs.push(mint_tokens(deps.storage, &_env.block, &state, &rcpt_addr, amount)?);
+    let hooks = prepare_balance_hooks(deps.storage, diffs)?;
+
+    let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_attribute("action", "mint")
+        .add_attribute("to", recipient)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+pub fn execute_mint_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    mints: Vec<Cw20Coin>,
+) -> Result<Response, ContractError> {
+    let recipients = validate_batch(deps.as_ref(), &mints)?;
+    assert_can_transfer(deps.storage, &recipients.iter().collect::<Vec<_>>())?;
+    assert_members(deps.as_ref(), &recipients.iter().collect::<Vec<_>>())?;
+    let total = mints.iter().map(|m| m.amount).sum::<Uint128>();
+
+    // caps and limits are checked against the whole batch, the period is rolled once
+    let (state, period_res) = issue_supply(&mut deps, env.clone(), &info.sender, total)?;
+
+    let mut diffs = settle_tiered_tax(deps.storage, &env.block, &recipients.iter().collect::<Vec<_>>())?;
+    for (rcpt_addr, mint) in recipients.iter().zip(&mints) {
+        diffs.push(mint_tokens(deps.storage, &env.block, &state, rcpt_addr, mint.amount)?);
+    }
+    let hooks = prepare_balance_hooks(deps.storage, diffs)?;
+
+    let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_attribute("action", "mint_batch")
+        .add_attribute("recipients", mints.len().to_string())
+        .add_attribute("amount", total);
+    Ok(res)
+}
+
+/// Check that minter may issue amount new tokens and add them to the total supply.
+/// Rolls the period forward and returns the resulting state.
+pub fn issue_supply(
+    deps: &mut DepsMut,
+    env: Env,
+    minter: &Addr,
+    amount: Uint128,
+) -> Result<(State, Response), ContractError> {
     let mut config = TOKEN_INFO
         .may_load(deps.storage)?
         .ok_or(ContractError::Unauthorized {})?;
@@ -383,15 +708,22 @@ pub fn execute_mint(
     .ok_or(ContractError::Unauthorized {})?;
 
 
-    if config
+    // the minter from TokenInfo is only bound by the global cap,
+    // anyone else must be registered as an additional minter
+    let is_main_minter = config
         .mint
         .as_ref()
-        .ok_or(ContractError::Unauthorized {})?
-        .minter
-        != info.sender
-    {
-        return Err(ContractError::Unauthorized {});
-    }
+        .map(|m| &m.minter == minter)
+        .unwrap_or(false);
+    let minter_config = if is_main_minter {
+        None
+    } else {
+        Some(
+            MINTERS
+                .may_load(deps.storage, minter)?
+                .ok_or(ContractError::Unauthorized {})?,
+        )
+    };
 
     // update supply and enforce cap
     config.total_supply += amount;
@@ -402,25 +734,114 @@ pub fn execute_mint(
     }
     TOKEN_INFO.save(deps.storage, &config)?;
 
-    change_period(&mut deps, _env, &mut state); 
+    let period_res = change_period(deps, env, &mut state)?; 
 
-    let base_value : u128;
-    base_value = to_base_amount(amount.u128(), state.demurrage_amount);
+    // additional minters must also respect their own cap and period limit
+    if let Some(mut config) = minter_config {
+        record_mint(&mut config, amount, state.current_period)?;
+        MINTERS.save(deps.storage, minter, &config)?;
+    }
 
+    Ok((state, period_res))
+}
 
-    // add amount to recipient balance
-    let rcpt_addr = deps.api.addr_validate(&recipient)?;
-    BALANCES.update(
-        deps.storage,
-        &rcpt_addr,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + Uint128::from(base_value)) },
+/// Credit newly issued tokens, as base amount of the current demurrage modifier
+pub fn mint_tokens(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    state: &State,
+    to: &Addr,
+    amount: Uint128,
+) -> StdResult<BalanceDiff> {
+    let base_value = Uint128::from(to_base_amount(amount.u128(), state.demurrage_amount));
+    let balance = balances().update(
+        storage,
+        to,
+        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + base_value) },
     )?;
+    let diff = BalanceDiff::credited(to, balance, base_value);
+    record_balance_changes(storage, block, std::slice::from_ref(&diff))?;
+    Ok(diff)
+}
 
-    let res = Response::new()
-        .add_attribute("action", "mint")
-        .add_attribute("to", recipient)
-        .add_attribute("amount", amount);
-    Ok(res)
+/// Count a mint against the limits of an additional minter
+fn record_mint(
+    minter: &mut MinterConfig,
+    amount: Uint128,
+    period: u64,
+) -> Result<(), ContractError> {
+    // per period usage starts over once a new period began
+    if minter.period != period {
+        minter.period = period;
+        minter.period_minted = Uint128::zero();
+    }
+
+    minter.minted = minter.minted.checked_add(amount).map_err(StdError::overflow)?;
+    if let Some(cap) = minter.cap {
+        if minter.minted > cap {
+            return Err(ContractError::CannotExceedCap {});
+        }
+    }
+
+    minter.period_minted = minter
+        .period_minted
+        .checked_add(amount)
+        .map_err(StdError::overflow)?;
+    if let Some(limit) = minter.per_period_limit {
+        if minter.period_minted > limit {
+            return Err(ContractError::CannotExceedPeriodLimit {});
+        }
+    }
+    Ok(())
+}
+
+pub fn execute_add_minter(
+    deps: DepsMut,
+    info: MessageInfo,
+    minter: String,
+    cap: Option<Uint128>,
+    per_period_limit: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let minter_addr = deps.api.addr_validate(&minter)?;
+    // keep the usage counters when only the limits of a minter change
+    MINTERS.update(deps.storage, &minter_addr, |config| -> StdResult<_> {
+        let config = config.unwrap_or(MinterConfig {
+            cap: None,
+            minted: Uint128::zero(),
+            per_period_limit: None,
+            period: 0,
+            period_minted: Uint128::zero(),
+        });
+        Ok(MinterConfig {
+            cap,
+            per_period_limit,
+            ..config
+        })
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_minter")
+        .add_attribute("minter", minter))
+}
+
+pub fn execute_remove_minter(
+    deps: DepsMut,
+    info: MessageInfo,
+    minter: String,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let minter_addr = deps.api.addr_validate(&minter)?;
+    if !MINTERS.has(deps.storage, &minter_addr) {
+        return Err(ContractError::MinterNotFound {});
+    }
+    MINTERS.remove(deps.storage, &minter_addr);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_minter")
+        .add_attribute("minter", minter))
 }
 
 pub fn execute_send(
@@ -436,20 +857,27 @@ pub fn execute_send(
     }
 
     let rcpt_addr = deps.api.addr_validate(&contract)?;
+    assert_can_transfer(deps.storage, &[&info.sender, &rcpt_addr])?;
+    assert_members(deps.as_ref(), &[&info.sender, &rcpt_addr])?;
+    let settled = settle_tiered_tax(deps.storage, &_env.block, &[&info.sender, &rcpt_addr])?;
 
     // move the tokens to the contract
-    BALANCES.update(
-        deps.storage,
-        &info.sender,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
-    BALANCES.update(
+    let sender_balance = debit_balance(deps.storage, &_env.block, &info.sender, amount)?;
+    let rcpt_balance = balances().update(
         deps.storage,
         &rcpt_addr,
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
+    let period = STATE.load(deps.storage)?.current_period;
+    consume_outflow(deps.storage, &_env.block, &info.sender, amount)?;
+    record_transfer(deps.storage, period, &info.sender, &rcpt_addr, amount)?;
+
+    let diffs = vec![
+        BalanceDiff::debited(&info.sender, sender_balance, amount),
+        BalanceDiff::credited(&rcpt_addr, rcpt_balance, amount),
+    ];
+    record_balance_changes(deps.storage, &_env.block, &diffs)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
 
     let res = Response::new()
         .add_attribute("action", "send")
@@ -463,39 +891,11 @@ pub fn execute_send(
                 msg,
             }
             .into_cosmos_msg(contract)?,
-        );
+        )
+        .add_submessages(hooks);
     Ok(res)
 }
 
-pub fn execute_update_minter(
-    deps: DepsMut,
-    _env: Env,
-    info: MessageInfo,
-    new_minter: String,
-) -> Result<Response, ContractError> {
-    let mut config = TOKEN_INFO
-        .may_load(deps.storage)?
-        .ok_or(ContractError::Unauthorized {})?;
-
-    let mint = config.mint.as_ref().ok_or(ContractError::Unauthorized {})?;
-    if mint.minter != info.sender {
-        return Err(ContractError::Unauthorized {});
-    }
-
-    let minter = deps.api.addr_validate(&new_minter)?;
-    let minter_data = MinterData {
-        minter,
-        cap: mint.cap,
-    };
-    config.mint = Some(minter_data);
-
-    TOKEN_INFO.save(deps.storage, &config)?;
-
-    Ok(Response::default()
-        .add_attribute("action", "update_minter")
-        .add_attribute("new_minter", new_minter))
-}
-
 //ALLOWANCE:__rust_force_expr!
 pub fn execute_increase_allowance(
     deps: DepsMut,
@@ -606,6 +1006,18 @@ pub fn deduct_allowance(
 }
 
 
+/// Prepare a BalanceChangedHookMsg for every registered hook
+pub fn prepare_balance_hooks(
+    storage: &dyn Storage,
+    diffs: Vec<BalanceDiff>,
+) -> StdResult<Vec<SubMsg>> {
+    let msg = BalanceChangedHookMsg::new(diffs);
+    HOOKS.prepare_hooks(storage, |h| {
+        msg.clone().into_cosmos_msg(h).map(SubMsg::new)
+    })
+}
+
+
 /*
     *******************
     *******************
@@ -620,31 +1032,42 @@ pub fn deduct_allowance(
 /// Apply Default Redistribution: all amounts go to sink address
 pub fn apply_default_redistribution(
     deps: &mut DepsMut,
+    block: &BlockInfo,
     state: &mut State,
     distribution: u128, 
 ) -> Result<Response, ContractError> {
     let sink_addr = deps.api.addr_validate(&state.sink_address)?;
 
-    BALANCES.update(
+    let sink_balance = balances().update(
         deps.storage,
         &sink_addr,
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + Uint128::from(distribution))},
     )?;  
 
+    // nothing was redistributed, so there is no balance change to report
+    let hooks = if distribution > 0 {
+        let diffs = vec![BalanceDiff::credited(&sink_addr, sink_balance, Uint128::from(distribution))];
+        record_balance_changes(deps.storage, block, &diffs)?;
+        prepare_balance_hooks(deps.storage, diffs)?
+    } else {
+        vec![]
+    };
+
     let res = Response::new()
+    .add_submessages(hooks)
     .add_attribute("action", "default_redistribution")
     .add_attribute("amount", distribution.to_string());
     
     Ok(res)
 
-    //BALANCES.update(
+    //balances().update(
     //     deps.storage,
     //     &info.sender,
     //     |balance: Option<Uint128>| -> StdResult<_> {
     //         Ok(balance.unwrap_or_default().checked_sub(amount)?)
     //     },
     // )?;
-    // BALANCES.update(
+    // balances().update(
     //     deps.storage,
     //     &rcpt_addr,
     //     |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + Uint128::from(base_value)) },
@@ -669,11 +1092,12 @@ pub fn demurrage_cycles(
 
 
 ///Recalculate the demurrage modifier for the new period
+///The returned response carries the redistribution hook messages
 pub fn change_period(
     deps: &mut DepsMut, 
     _env: Env, 
     state: &mut State,
-) -> Result<bool, ContractError> {
+) -> Result<Response, ContractError> {
     //take current timestamp
     let current_timestamp: Timestamp = _env.block.time;
     println!("change period, demurrage_amount : {}", state.demurrage_amount);
@@ -685,13 +1109,20 @@ pub fn change_period(
 
     println!("change period,state.current_period: {}", state.current_period);
     
-    let next_period: u64 = state.current_period + 1;
+    // after idle time all missed periods are closed at once, so every transaction
+    // within the same wall-clock period sees the same period number
+    let next_period: u64 = actual_period(current_timestamp, state) as u64;
 
     println!("change period, state.current_period: {}", state.current_period);
     println!("change period, period_minute: {}", state.period_minute);
     println!("change period, start_timestamp: {}", state.start_timestamp);
 
     let period_timestamp: Timestamp = get_period_time_delta(state.start_timestamp, state.current_period, state.period_minute);
+
+    // the current period did not end yet, there is nothing to roll over
+    if next_period <= state.current_period {
+        return Ok(Response::new());
+    }
     
     println!("change period, current timestamp: {}", current_timestamp.seconds());
     println!("change period, period timestamp: {} ", period_timestamp.seconds());
@@ -717,21 +1148,28 @@ pub fn change_period(
 
     //update the demurrage state 
     //state.demurrage_amount = next_demurrage_amount;
+    let closed_period = state.current_period;
     state.current_period = next_period;
     STATE.save(deps.storage, &state);
+    roll_over_metrics(deps.storage, next_period)?;
 
-    //send the tax to the sink account 
+    //send the tax to the sink account, the modifier already holds the decay of
+    //every missed period so they are redistributed in one step
     let distribution = get_distribution(deps, state)?;
-    let res = apply_default_redistribution(deps, state, distribution);
+    // with spending rebates, part of it stays for the spenders of the closed period
+    let distribution = reserve_rebates(deps.storage, closed_period, distribution)?;
+    // with a UBI group, the rest waits for the next distribution round
+    let distribution = reserve_ubi(deps.storage, distribution)?;
+    let res = apply_default_redistribution(deps, &_env.block, state, distribution)?;
 
-    return Ok(true);
+    Ok(res)
 }
 
 
 ///Get the demurrage period of the current block number
 pub fn actual_period(
     now_timestamp: Timestamp, // _env.block.time
-    state: &mut State,
+    state: &State,
 )-> u128 {
     return u128::from((now_timestamp.seconds()- state.start_timestamp.seconds())/ state.period_minute + 1);
 }
@@ -750,11 +1188,12 @@ pub fn get_distribution(
     println!("get_distribution, total supply: {}", config.total_supply.u128());
     println!("get_distribution, demurrage_amount: {}", state.demurrage_amount);
 
-    let difference : u128;
-    difference = config.total_supply.u128() * (RESOLUTION_FACTOR- (state.demurrage_amount * 100000));
-    
-    
-    return Ok(difference/ RESOLUTION_FACTOR);
+    return Ok(distribution_of(config.total_supply.u128(), state.demurrage_amount));
+}
+
+/// Demurrage taken from total_supply at the given modifier
+pub fn distribution_of(total_supply: u128, demurrage_amount: u128) -> u128 {
+    total_supply * (RESOLUTION_FACTOR - demurrage_amount * 100000) / RESOLUTION_FACTOR
 }
 
 ///Default apply demurrage function, no limitations on number of periods 
@@ -822,7 +1261,7 @@ fn get_period_time_delta (
 
 //check please if it needs to fix the 100000000 value
 /// Inflates the given amount according to the current demurrage modifier
-fn to_base_amount(
+pub fn to_base_amount(
     value: u128,
     demurrage_amount: u128
 )-> u128{
@@ -831,8 +1270,17 @@ fn to_base_amount(
 }
 
 
+/// Current worth of a stored balance amount, the inverse of to_base_amount
+pub fn from_base_amount(
+    base: Uint128,
+    demurrage_amount: u128
+) -> Uint128 {
+    base.multiply_ratio(demurrage_amount * 100000, RESOLUTION_FACTOR)
+}
+
+
 /// Calculate the time delta in whole minutes passed between given timestamp and current timestamp
-fn get_minutes_delta (
+pub fn get_minutes_delta (
     now_timestamp: Timestamp, 
     last_timestamp: Timestamp
 ) -> u64{
@@ -861,7 +1309,7 @@ fn grow_by (
     return (value_factor * value) / GROWTH_RESOLUTION_FACTOR;
 }
 
-fn decay_by (
+pub fn decay_by (
     value: u128, 
     tax_level: u128,
     period: u64, 
@@ -904,6 +1352,9 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
         QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
         QueryMsg::Minter {} => to_binary(&query_minter(deps)?),
+        QueryMsg::Minters { start_after, limit } => {
+            to_binary(&query_minters(deps, start_after, limit)?)
+        }
         QueryMsg::Allowance { owner, spender } => {
             to_binary(&query_allowance(deps, owner, spender)?)
         }
@@ -919,6 +1370,61 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::DemurrageAmount {} => to_binary(&query_demurrage_amount(deps)?),
         QueryMsg::TaxLevel {} => to_binary(&query_tax_level(deps)?), 
         QueryMsg::SinkAccount {} => to_binary(&query_sink_address(deps)?), 
+        QueryMsg::Vesting { address } => to_binary(&query_vesting(deps, _env, address)?),
+        QueryMsg::Nonce { owner } => to_binary(&query_nonce(deps, owner)?),
+        QueryMsg::Reserve {} => to_binary(&query_reserve(deps)?),
+        QueryMsg::FrozenAccounts { start_after, limit } => {
+            to_binary(&query_frozen_accounts(deps, start_after, limit)?)
+        }
+        QueryMsg::ProjectedBalance { address, at } => {
+            to_binary(&query_projected_balance(deps, _env, address, at)?)
+        }
+        QueryMsg::TaxDue { address, until } => {
+            to_binary(&query_tax_due(deps, _env, address, until)?)
+        }
+        QueryMsg::AllBalances { start_after, limit } => {
+            to_binary(&query_all_balances(deps, _env, start_after, limit)?)
+        }
+        QueryMsg::TopHolders { limit } => to_binary(&query_top_holders(deps, _env, limit)?),
+        QueryMsg::AccountStats { address } => {
+            to_binary(&query_account_stats(deps, _env, address)?)
+        }
+        QueryMsg::AccountsByClass {
+            class,
+            start_after,
+            limit,
+        } => to_binary(&query_accounts_by_class(deps, class, start_after, limit)?),
+        QueryMsg::TaxableBalance { address } => {
+            to_binary(&query_taxable_balance(deps, _env, address)?)
+        }
+        QueryMsg::TaxTiers {} => to_binary(&query_tax_tiers(deps)?),
+        QueryMsg::PendingRebate { address } => to_binary(&query_pending_rebate(deps, address)?),
+        QueryMsg::TwabBalance { address, from, to } => {
+            to_binary(&query_twab_balance(deps, _env, address, from, to)?)
+        }
+        QueryMsg::RateLimit { address } => to_binary(&query_rate_limit(deps, _env, address)?),
+        QueryMsg::MembershipGroup {} => to_binary(&query_membership_group(deps)?),
+        QueryMsg::UbiStatus {} => to_binary(&query_ubi_status(deps)?),
+        QueryMsg::PendingOwnership {} => to_binary(&query_pending_ownership(deps)?),
+        QueryMsg::Simulate { sender, msg } => {
+            to_binary(&query_simulate(deps, _env, sender, msg)?)
+        }
+        QueryMsg::Scheduled { sender, start_after, limit } => {
+            to_binary(&query_scheduled(deps, sender, start_after, limit)?)
+        }
+        QueryMsg::SubscriptionsByPayer { payer, start_after, limit } => {
+            to_binary(&query_subscriptions_by_payer(deps, payer, start_after, limit)?)
+        }
+        QueryMsg::SubscriptionsByPayee { payee, start_after, limit } => {
+            to_binary(&query_subscriptions_by_payee(deps, payee, start_after, limit)?)
+        }
+        QueryMsg::PeriodMetrics { period } => to_binary(&query_period_metrics(deps, period)?),
+        QueryMsg::Locks { address } => to_binary(&query_locks(deps, _env, address)?),
+        QueryMsg::SavingsConfig {} => to_binary(&query_savings_config(deps)?),
+        QueryMsg::Paused {} => to_binary(&query_paused(deps)?),
+        QueryMsg::Guardian {} => to_binary(&GUARDIAN.query_admin(deps)?),
+        QueryMsg::Admin {} => to_binary(&ADMIN.query_admin(deps)?),
+        QueryMsg::Hooks {} => to_binary(&HOOKS.query_hooks(deps)?),
         
     }
 }
@@ -939,7 +1445,7 @@ pub fn query_sink_address(deps: Deps) -> StdResult<SinkAddressResponse> {
 
 pub fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse> {
     let address = deps.api.addr_validate(&address)?;
-    let balance = BALANCES
+    let balance = balances()
         .may_load(deps.storage, &address)?
         .unwrap_or_default();
     Ok(BalanceResponse { balance })
@@ -983,7 +1489,7 @@ mod tests {
     use cosmwasm_std::testing::{
         mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info,
     };
-    use cosmwasm_std::{coins, from_binary, Addr, CosmosMsg, StdError, SubMsg, WasmMsg};
+    use cosmwasm_std::{coins, from_binary, Addr, CosmosMsg, Order, StdError, SubMsg, WasmMsg};
 
     use super::*;
     use crate::msg::InstantiateMarketingInfo;
@@ -992,6 +1498,14 @@ mod tests {
         query_balance(deps, address.into()).unwrap().balance
     }
 
+    // stored balances of all accounts added up
+    fn sum_balances(deps: Deps) -> Uint128 {
+        balances()
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.unwrap().1)
+            .sum()
+    }
+
     // this will set up the instantiation for other tests
     fn do_instantiate_with_minter(
         deps: DepsMut,
@@ -1016,6 +1530,15 @@ mod tests {
         _do_instantiate(deps, addr, amount, None)
     }
 
+    // plain transfer of amount between two accounts
+    fn do_transfer(deps: DepsMut, env: Env, from: &str, to: &str, amount: u128) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Transfer {
+            recipient: to.to_string(),
+            amount: Uint128::new(amount),
+        };
+        execute(deps, env, mock_info(from, &[]), msg)
+    }
+
     // this will set up the instantiation for other tests
     fn _do_instantiate(
         mut deps: DepsMut,
@@ -1032,6 +1555,9 @@ mod tests {
                 amount,
             }],
             mint: mint.clone(),
+            admin: Some("creator".to_string()),
+            initial_vesting: vec![],
+            reserve: None,
             
             //0.000050105908373373
             tax_level_minute: 501059083733730000, // 38 decimals
@@ -1076,6 +1602,9 @@ mod tests {
                     amount,
                 }],
                 mint: None,
+                admin: None,
+                initial_vesting: vec![],
+                reserve: None,
                 tax_level_minute: 5010590837337300, // 38 decimals
                 period_minutes: 1, 
                 supply_cap: 10000000, //supply cap is 10 million coins
@@ -1119,6 +1648,9 @@ mod tests {
                     minter: minter.clone(),
                     cap: Some(limit),
                 }),
+                admin: None,
+                initial_vesting: vec![],
+                reserve: None,
                 tax_level_minute: 5010590837337300, // 38 decimals
                 period_minutes: 1, 
                 supply_cap: 10000000, //supply cap is 10 million coins
@@ -1169,6 +1701,9 @@ mod tests {
                     minter,
                     cap: Some(limit),
                 }),
+                admin: None,
+                initial_vesting: vec![],
+                reserve: None,
                 tax_level_minute: 5010590837337300, // 38 decimals
                 period_minutes: 1, 
                 supply_cap: 10000000, //supply cap is 10 million coins
@@ -1275,6 +1810,9 @@ mod tests {
         let env = mock_env();
         let res = execute(deps.as_mut(), env.clone(), info, msg);
         assert!(res.is_ok());
+        // the new minter takes over once it accepts
+        let info = mock_info(new_minter, &[]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AcceptMinter {}).unwrap();
         let query_minter_msg = QueryMsg::Minter {};
         let res = query(deps.as_ref(), env, query_minter_msg);
         let mint: MinterResponse = from_binary(&res.unwrap()).unwrap();
@@ -1347,6 +1885,9 @@ mod tests {
                 },
             ],
             mint: None,
+            admin: None,
+            initial_vesting: vec![],
+            reserve: None,
             tax_level_minute: 5010590837337300, // 38 decimals
             period_minutes: 1, 
             supply_cap: 10000000, //supply cap is 10 million coins
@@ -1373,6 +1914,9 @@ mod tests {
                 },
             ],
             mint: None,
+            admin: None,
+            initial_vesting: vec![],
+            reserve: None,
             tax_level_minute: 5010590837337300, // 38 decimals
             period_minutes: 1, 
             supply_cap: 10000000, //supply cap is 10 million coins
@@ -1653,6 +2197,9 @@ mod tests {
                     },
                 ],
                 mint: None,
+                admin: None,
+                initial_vesting: vec![],
+                reserve: None,
                 tax_level_minute: 5010590837337300000000, // 38 decimals
                 period_minutes: 1, 
                 supply_cap: 10000000, //supply cap is 10 million coins
@@ -1694,4 +2241,2330 @@ mod tests {
 
 
     }
-}
\ No newline at end of file
+
+    mod hooks {
+        use super::*;
+        use crate::hook::{BalanceChangedHookMsg, BalanceDiff};
+        use cw_controllers::{AdminError, HookError};
+
+        #[test]
+        fn only_admin_manages_hooks() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "addr0001", Uint128::new(1234));
+
+            let msg = ExecuteMsg::AddHook {
+                addr: "rewards".to_string(),
+            };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg.clone())
+                .unwrap_err();
+            assert_eq!(err, ContractError::Hook(HookError::Admin(AdminError::NotAdmin {})));
+
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap();
+            let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::Hook(HookError::HookAlreadyRegistered {}));
+
+            let hooks = HOOKS.query_hooks(deps.as_ref()).unwrap();
+            assert_eq!(hooks.hooks, vec!["rewards".to_string()]);
+
+            let msg = ExecuteMsg::RemoveHook {
+                addr: "rewards".to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let hooks = HOOKS.query_hooks(deps.as_ref()).unwrap();
+            assert!(hooks.hooks.is_empty());
+        }
+
+        #[test]
+        fn balance_changes_notify_hooks() {
+            let mut deps = mock_dependencies();
+            let addr1 = String::from("addr0001");
+            let addr2 = String::from("addr0002");
+            let amount1 = Uint128::new(12340000);
+            do_instantiate(deps.as_mut(), &addr1, amount1);
+
+            let msg = ExecuteMsg::AddHook {
+                addr: "rewards".to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            // transfer reports both sides
+            let transfer = Uint128::new(76543);
+            let msg = ExecuteMsg::Transfer {
+                recipient: addr2.clone(),
+                amount: transfer,
+            };
+            let res = execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap();
+            let expected = BalanceChangedHookMsg::new(vec![
+                BalanceDiff::new(&addr1, amount1, amount1 - transfer),
+                BalanceDiff::new(&addr2, Uint128::zero(), transfer),
+            ]);
+            assert_eq!(
+                res.messages,
+                vec![SubMsg::new(expected.into_cosmos_msg("rewards").unwrap())]
+            );
+
+            // burn reports the burner
+            let burn = Uint128::new(43);
+            let msg = ExecuteMsg::Burn { amount: burn };
+            let res = execute(deps.as_mut(), mock_env(), mock_info(&addr2, &[]), msg).unwrap();
+            let expected = BalanceChangedHookMsg::one(BalanceDiff::new(
+                &addr2,
+                transfer,
+                transfer - burn,
+            ));
+            assert_eq!(
+                res.messages,
+                vec![SubMsg::new(expected.into_cosmos_msg("rewards").unwrap())]
+            );
+        }
+    }
+
+    mod minters {
+        use super::*;
+        use crate::query::{MinterInfo, MintersResponse};
+        use cw_controllers::AdminError;
+
+        fn add_minter(deps: DepsMut, sender: &str, minter: &str) -> Result<Response, ContractError> {
+            let msg = ExecuteMsg::AddMinter {
+                minter: minter.to_string(),
+                cap: Some(Uint128::new(1000)),
+                per_period_limit: Some(Uint128::new(300)),
+            };
+            execute(deps, mock_env(), mock_info(sender, &[]), msg)
+        }
+
+        fn mint(deps: DepsMut, env: Env, minter: &str, amount: u128) -> Result<Response, ContractError> {
+            let msg = ExecuteMsg::Mint {
+                recipient: "lucky".to_string(),
+                amount: Uint128::new(amount),
+            };
+            execute(deps, env, mock_info(minter, &[]), msg)
+        }
+
+        #[test]
+        fn only_admin_manages_minters() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "genesis", Uint128::new(1234));
+
+            let err = add_minter(deps.as_mut(), "genesis", "coop").unwrap_err();
+            assert_eq!(err, ContractError::Admin(AdminError::NotAdmin {}));
+
+            add_minter(deps.as_mut(), "creator", "coop").unwrap();
+            mint(deps.as_mut(), mock_env(), "coop", 100).unwrap();
+
+            let msg = ExecuteMsg::RemoveMinter {
+                minter: "coop".to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap();
+            let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::MinterNotFound {});
+
+            let err = mint(deps.as_mut(), mock_env(), "coop", 100).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+
+        #[test]
+        fn minters_respect_cap_and_period_limit() {
+            let mut deps = mock_dependencies();
+            do_instantiate_with_minter(deps.as_mut(), "genesis", Uint128::new(1234), "minter", None);
+            add_minter(deps.as_mut(), "creator", "coop").unwrap();
+
+            // the original minter keeps working and is still reported
+            mint(deps.as_mut(), mock_env(), "minter", 5000).unwrap();
+            assert_eq!(query_minter(deps.as_ref()).unwrap().unwrap().minter, "minter");
+
+            let mut env = mock_env();
+            mint(deps.as_mut(), env.clone(), "coop", 300).unwrap();
+            let err = mint(deps.as_mut(), env.clone(), "coop", 1).unwrap_err();
+            assert_eq!(err, ContractError::CannotExceedPeriodLimit {});
+
+            // limit resets in the next period
+            for _ in 0..2 {
+                env.block.time = env.block.time.plus_seconds(60);
+                mint(deps.as_mut(), env.clone(), "coop", 300).unwrap();
+            }
+            env.block.time = env.block.time.plus_seconds(60);
+            let err = mint(deps.as_mut(), env.clone(), "coop", 300).unwrap_err();
+            assert_eq!(err, ContractError::CannotExceedCap {});
+
+            let res = query(
+                deps.as_ref(),
+                env,
+                QueryMsg::Minters {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+            let minters: MintersResponse = from_binary(&res).unwrap();
+            assert_eq!(
+                minters.minters,
+                vec![MinterInfo {
+                    minter: "coop".to_string(),
+                    cap: Some(Uint128::new(1000)),
+                    minted: Uint128::new(900),
+                    per_period_limit: Some(Uint128::new(300)),
+                    period_minted: Uint128::zero(),
+                }]
+            );
+        }
+
+        #[test]
+        fn idle_periods_are_closed_at_once() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "genesis", Uint128::new(1234));
+            add_minter(deps.as_mut(), "creator", "coop").unwrap();
+
+            let mut env = mock_env();
+            mint(deps.as_mut(), env.clone(), "coop", 300).unwrap();
+            let period = STATE.load(&deps.storage).unwrap().current_period;
+
+            // several periods pass without any transaction
+            env.block.time = env.block.time.plus_seconds(5 * 60);
+            mint(deps.as_mut(), env.clone(), "coop", 300).unwrap();
+            assert_eq!(STATE.load(&deps.storage).unwrap().current_period, period + 5);
+
+            // a second mint in the same block is still within the same period
+            let err = mint(deps.as_mut(), env.clone(), "coop", 1).unwrap_err();
+            assert_eq!(err, ContractError::CannotExceedPeriodLimit {});
+            assert_eq!(STATE.load(&deps.storage).unwrap().current_period, period + 5);
+        }
+    }
+
+    mod reserve {
+        use super::*;
+        use crate::msg::InstantiateReserveInfo;
+        use crate::query::ReserveResponse;
+        use cosmwasm_std::{coin, BankMsg, ContractResult, Decimal, SystemError, SystemResult, WasmQuery};
+        use cw_utils::PaymentError;
+
+        fn do_instantiate_with_reserve(deps: DepsMut, tax_level_minute: u128) {
+            let instantiate_msg = InstantiateMsg {
+                name: "Backed Token".to_string(),
+                symbol: "BACK".to_string(),
+                decimals: 6,
+                initial_balances: vec![],
+                mint: None,
+                admin: Some("creator".to_string()),
+                initial_vesting: vec![],
+                reserve: Some(InstantiateReserveInfo {
+                    denom: "ucosm".to_string(),
+                    ratio: Decimal::from_ratio(2u128, 1u128),
+                    redemption_fee: Decimal::percent(10),
+                }),
+                tax_level_minute, // 38 decimals
+                period_minutes: 1,
+                supply_cap: 10000000, //supply cap is 10 million coins
+                sink_address: "sinkaddress".to_string(),
+            };
+            instantiate(deps, mock_env(), mock_info("creator", &[]), instantiate_msg).unwrap();
+        }
+
+        #[test]
+        fn deposit_and_redeem() {
+            let mut deps = mock_dependencies();
+            do_instantiate_with_reserve(deps.as_mut(), 5010590837337300);
+
+            // only the reserve denom is accepted
+            let info = mock_info("holder", &coins(500, "uatom"));
+            let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap_err();
+            assert_eq!(err, ContractError::Payment(PaymentError::MissingDenom("ucosm".to_string())));
+
+            let info = mock_info("holder", &coins(500, "ucosm"));
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "holder"), Uint128::new(1000));
+            assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128::new(1000));
+
+            // 10% of the redeemed tokens go to the sink, the rest is paid out at 2:1
+            let msg = ExecuteMsg::Redeem {
+                amount: Uint128::new(400),
+            };
+            let res = execute(deps.as_mut(), mock_env(), mock_info("holder", &[]), msg).unwrap();
+            assert_eq!(
+                res.messages,
+                vec![SubMsg::new(BankMsg::Send {
+                    to_address: "holder".to_string(),
+                    amount: vec![coin(180, "ucosm")],
+                })]
+            );
+            assert_eq!(get_balance(deps.as_ref(), "holder"), Uint128::new(600));
+            assert_eq!(get_balance(deps.as_ref(), "sinkaddress"), Uint128::new(40));
+            assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128::new(640));
+
+            let res = query(deps.as_ref(), mock_env(), QueryMsg::Reserve {}).unwrap();
+            let reserve: ReserveResponse = from_binary(&res).unwrap();
+            assert_eq!(reserve.reserve, Uint128::new(320));
+            assert_eq!(reserve.denom, "ucosm");
+        }
+
+        #[test]
+        fn redeem_pays_out_current_worth() {
+            let mut deps = mock_dependencies();
+            // 0.01% per minute
+            do_instantiate_with_reserve(deps.as_mut(), 1_000_000_000_000_000_000_000_000);
+            let info = mock_info("holder", &coins(500, "ucosm"));
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+
+            // the periods missed since are closed before the payout is computed
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(6000);
+            let msg = ExecuteMsg::Redeem {
+                amount: Uint128::new(400),
+            };
+            let res = execute(deps.as_mut(), env.clone(), mock_info("holder", &[]), msg.clone()).unwrap();
+            let send = BankMsg::Send {
+                to_address: "holder".to_string(),
+                amount: vec![coin(178, "ucosm")],
+            };
+            assert!(res.messages.contains(&SubMsg::new(send)));
+            // the supply drops by the worth burned, in the unit the deposit added
+            let state = STATE.load(&deps.storage).unwrap();
+            let burned = from_base_amount(Uint128::new(360), state.demurrage_amount);
+            assert!(burned < Uint128::new(360));
+            assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128::new(1000) - burned);
+
+            // with a membership group the sink must be a member to take the fee
+            deps.querier.update_wasm(|query| match query {
+                WasmQuery::Raw { contract_addr, key } if contract_addr == "group" => {
+                    let weight = if key.ends_with(b"holder") { to_binary(&1u64).unwrap() } else { Binary::default() };
+                    SystemResult::Ok(ContractResult::Ok(weight))
+                }
+                _ => SystemResult::Err(SystemError::Unknown {}),
+            });
+            let group = ExecuteMsg::SetMembershipGroup { group: Some("group".to_string()) };
+            execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), group).unwrap();
+            let err = execute(deps.as_mut(), env, mock_info("holder", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::NotMember { address: "sinkaddress".to_string() });
+        }
+
+        #[test]
+        fn no_reserve_configured() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "holder", Uint128::new(1234));
+
+            let info = mock_info("holder", &coins(500, "ucosm"));
+            let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap_err();
+            assert_eq!(err, ContractError::NoReserve {});
+
+            let msg = ExecuteMsg::Redeem {
+                amount: Uint128::new(100),
+            };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("holder", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::NoReserve {});
+        }
+    }
+
+    mod batch {
+        use super::*;
+
+        #[test]
+        fn transfer_batch() {
+            let mut deps = mock_dependencies();
+            let amount1 = Uint128::new(12340000);
+            do_instantiate(deps.as_mut(), "addr0001", amount1);
+            let info = mock_info("addr0001", &[]);
+
+            let msg = ExecuteMsg::TransferBatch { transfers: vec![] };
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::EmptyBatch {});
+
+            // a single invalid entry fails the whole batch
+            let msg = ExecuteMsg::TransferBatch {
+                transfers: vec![
+                    Cw20Coin {
+                        address: "addr0002".to_string(),
+                        amount: Uint128::new(100),
+                    },
+                    Cw20Coin {
+                        address: "addr0003".to_string(),
+                        amount: Uint128::zero(),
+                    },
+                ],
+            };
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidZeroAmount {});
+
+            let msg = ExecuteMsg::TransferBatch {
+                transfers: vec![
+                    Cw20Coin {
+                        address: "addr0002".to_string(),
+                        amount: Uint128::new(100),
+                    },
+                    Cw20Coin {
+                        address: "addr0003".to_string(),
+                        amount: Uint128::new(250),
+                    },
+                ],
+            };
+            let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            assert_eq!(res.attributes[0], attr("action", "transfer_batch"));
+            assert_eq!(res.attributes[3], attr("amount", "350"));
+            assert_eq!(get_balance(deps.as_ref(), "addr0001"), amount1 - Uint128::new(350));
+            assert_eq!(get_balance(deps.as_ref(), "addr0002"), Uint128::new(100));
+            assert_eq!(get_balance(deps.as_ref(), "addr0003"), Uint128::new(250));
+        }
+
+        #[test]
+        fn mint_batch_respects_cap() {
+            let mut deps = mock_dependencies();
+            let genesis = Uint128::new(1000);
+            let cap = Some(Uint128::new(2000));
+            do_instantiate_with_minter(deps.as_mut(), "genesis", genesis, "minter", cap);
+
+            let mints = vec![
+                Cw20Coin {
+                    address: "addr0001".to_string(),
+                    amount: Uint128::new(600),
+                },
+                Cw20Coin {
+                    address: "addr0002".to_string(),
+                    amount: Uint128::new(600),
+                },
+            ];
+            let msg = ExecuteMsg::MintBatch {
+                mints: mints.clone(),
+            };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("genesis", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            let msg = ExecuteMsg::MintBatch { mints };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::CannotExceedCap {});
+
+            let msg = ExecuteMsg::MintBatch {
+                mints: vec![
+                    Cw20Coin {
+                        address: "addr0001".to_string(),
+                        amount: Uint128::new(600),
+                    },
+                    Cw20Coin {
+                        address: "addr0002".to_string(),
+                        amount: Uint128::new(400),
+                    },
+                ],
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "addr0001"), Uint128::new(600));
+            assert_eq!(get_balance(deps.as_ref(), "addr0002"), Uint128::new(400));
+            assert_eq!(
+                query_token_info(deps.as_ref()).unwrap().total_supply,
+                Uint128::new(2000)
+            );
+        }
+    }
+
+    mod permit {
+        use super::*;
+        use crate::msg::Permit;
+        use crate::permit::{pubkey_to_address, PermitSignDoc};
+        use crate::query::NonceResponse;
+        use cosmwasm_std::to_vec;
+        use k256::ecdsa::signature::Signer;
+        use k256::ecdsa::{Signature, SigningKey};
+
+        fn signing_key() -> SigningKey {
+            SigningKey::from_bytes(&[7u8; 32]).unwrap()
+        }
+
+        fn sign(key: &SigningKey, env: &Env, permit: &Permit) -> Binary {
+            let doc = PermitSignDoc {
+                chain_id: env.block.chain_id.clone(),
+                contract: env.contract.address.to_string(),
+                permit: permit.clone(),
+            };
+            let signature: Signature = key.sign(&to_vec(&doc).unwrap());
+            Binary::from(signature.as_ref())
+        }
+
+        #[test]
+        fn relayer_transfers_with_permit() {
+            let mut deps = mock_dependencies();
+            let key = signing_key();
+            let pubkey = Binary::from(key.verifying_key().to_bytes().as_slice());
+            let owner = pubkey_to_address(&pubkey, "cosmos").unwrap();
+            let amount1 = Uint128::new(12340000);
+            do_instantiate(deps.as_mut(), &owner, amount1);
+
+            let env = mock_env();
+            let permit = Permit {
+                owner: owner.clone(),
+                recipient: "addr0002".to_string(),
+                amount: Uint128::new(500),
+                nonce: 0,
+                expires: Expiration::AtHeight(env.block.height + 10),
+            };
+            let msg = ExecuteMsg::TransferWithPermit {
+                permit: permit.clone(),
+                signature: sign(&key, &env, &permit),
+                pubkey: pubkey.clone(),
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("relayer", &[]), msg.clone()).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), &owner), amount1 - Uint128::new(500));
+            assert_eq!(get_balance(deps.as_ref(), "addr0002"), Uint128::new(500));
+
+            // the same permit cannot be replayed
+            let err = execute(deps.as_mut(), env.clone(), mock_info("relayer", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidNonce { expected: 1 });
+            let res = query(deps.as_ref(), env, QueryMsg::Nonce { owner }).unwrap();
+            let nonce: NonceResponse = from_binary(&res).unwrap();
+            assert_eq!(nonce.nonce, 1);
+        }
+
+        #[test]
+        fn invalid_permits_are_rejected() {
+            let mut deps = mock_dependencies();
+            let key = signing_key();
+            let pubkey = Binary::from(key.verifying_key().to_bytes().as_slice());
+            let owner = pubkey_to_address(&pubkey, "cosmos").unwrap();
+            do_instantiate(deps.as_mut(), &owner, Uint128::new(12340000));
+
+            let env = mock_env();
+            let permit = Permit {
+                owner: owner.clone(),
+                recipient: "addr0002".to_string(),
+                amount: Uint128::new(500),
+                nonce: 0,
+                expires: Expiration::AtHeight(env.block.height + 10),
+            };
+            let signature = sign(&key, &env, &permit);
+
+            // tampered amount
+            let msg = ExecuteMsg::TransferWithPermit {
+                permit: Permit {
+                    amount: Uint128::new(5000),
+                    ..permit.clone()
+                },
+                signature: signature.clone(),
+                pubkey: pubkey.clone(),
+            };
+            let err = execute(deps.as_mut(), env.clone(), mock_info("relayer", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidSignature {});
+
+            // key of someone else
+            let other = SigningKey::from_bytes(&[9u8; 32]).unwrap();
+            let msg = ExecuteMsg::TransferWithPermit {
+                permit: permit.clone(),
+                signature: sign(&other, &env, &permit),
+                pubkey: Binary::from(other.verifying_key().to_bytes().as_slice()),
+            };
+            let err = execute(deps.as_mut(), env.clone(), mock_info("relayer", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::PubkeyMismatch {});
+
+            // expired
+            let mut later = env;
+            later.block.height += 11;
+            let msg = ExecuteMsg::TransferWithPermit {
+                permit,
+                signature,
+                pubkey,
+            };
+            let err = execute(deps.as_mut(), later, mock_info("relayer", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::PermitExpired {});
+        }
+    }
+
+    mod vesting {
+        use super::*;
+        use crate::msg::{InitialVesting, VestingSchedule};
+        use crate::state::VESTING;
+        use crate::query::VestingResponse;
+        use cosmwasm_std::{from_slice, to_vec};
+        use cw_utils::Scheduled;
+
+        fn do_instantiate_with_vesting(deps: DepsMut, env: Env, schedule: VestingSchedule) {
+            let instantiate_msg = InstantiateMsg {
+                name: "Vested Token".to_string(),
+                symbol: "VEST".to_string(),
+                decimals: 6,
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: "founder".to_string(),
+                        amount: Uint128::new(1000),
+                    },
+                    Cw20Coin {
+                        address: "member".to_string(),
+                        amount: Uint128::new(1000),
+                    },
+                ],
+                initial_vesting: vec![InitialVesting {
+                    address: "founder".to_string(),
+                    schedule,
+                }],
+                mint: Some(MinterResponse {
+                    minter: "minter".to_string(),
+                    cap: None,
+                }),
+                admin: None,
+                reserve: None,
+                tax_level_minute: 5010590837337300, // 38 decimals
+                period_minutes: 1,
+                supply_cap: 10000000, //supply cap is 10 million coins
+                sink_address: "sinkaddress".to_string(),
+            };
+            instantiate(deps, env, mock_info("creator", &[]), instantiate_msg).unwrap();
+        }
+
+        #[test]
+        fn initial_balance_vests_after_cliff() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let start = env.block.time;
+            let schedule = VestingSchedule {
+                cliff: Scheduled::AtTime(start.plus_seconds(100)),
+                end: Scheduled::AtTime(start.plus_seconds(1000)),
+            };
+            do_instantiate_with_vesting(deps.as_mut(), env.clone(), schedule);
+
+            // everything is locked before the cliff, others are not affected
+            let err = do_transfer(deps.as_mut(), env.clone(), "founder", "shop", 1).unwrap_err();
+            assert_eq!(err, ContractError::LockedTokens { locked: Uint128::new(1000) });
+            do_transfer(deps.as_mut(), env.clone(), "member", "shop", 1).unwrap();
+
+            // linear release after the cliff
+            let mut later = env.clone();
+            later.block.time = start.plus_seconds(400);
+            let res = query(
+                deps.as_ref(),
+                later.clone(),
+                QueryMsg::Vesting {
+                    address: "founder".to_string(),
+                },
+            )
+            .unwrap();
+            let vesting: VestingResponse = from_binary(&res).unwrap();
+            assert_eq!(vesting.locked, Uint128::new(600));
+
+            do_transfer(deps.as_mut(), later.clone(), "founder", "shop", 400).unwrap();
+            let err = do_transfer(deps.as_mut(), later.clone(), "founder", "shop", 1).unwrap_err();
+            assert_eq!(err, ContractError::LockedTokens { locked: Uint128::new(600) });
+
+            // fully released at the end
+            later.block.time = start.plus_seconds(1000);
+            do_transfer(deps.as_mut(), later, "founder", "shop", 600).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "founder"), Uint128::zero());
+            // the released schedule is dropped
+            assert!(!VESTING.has(&deps.storage, &Addr::unchecked("founder")));
+        }
+
+        #[test]
+        fn initial_vesting_is_optional() {
+            let msg = InstantiateMsg {
+                name: "Cash Token".to_string(),
+                symbol: "CASH".to_string(),
+                decimals: 6,
+                initial_balances: vec![],
+                initial_vesting: vec![],
+                mint: None,
+                admin: None,
+                reserve: None,
+                tax_level_minute: 5010590837337300, // 38 decimals
+                period_minutes: 1,
+                supply_cap: 10000000,
+                sink_address: "sinkaddress".to_string(),
+            };
+            // a message from before vesting existed
+            let json = String::from_utf8(to_vec(&msg).unwrap()).unwrap().replace(r#""initial_vesting":[],"#, "");
+            assert!(!json.contains("initial_vesting"));
+            assert_eq!(from_slice::<InstantiateMsg>(json.as_bytes()).unwrap(), msg);
+        }
+
+        #[test]
+        fn mint_vesting() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let schedule = VestingSchedule {
+                cliff: Scheduled::AtHeight(env.block.height + 10),
+                end: Scheduled::AtHeight(env.block.height + 20),
+            };
+            do_instantiate_with_vesting(deps.as_mut(), env.clone(), schedule.clone());
+
+            // cannot mix heights and times
+            let msg = ExecuteMsg::MintVesting {
+                recipient: "member".to_string(),
+                amount: Uint128::new(500),
+                schedule: VestingSchedule {
+                    cliff: Scheduled::AtHeight(env.block.height + 10),
+                    end: Scheduled::AtTime(env.block.time.plus_seconds(100)),
+                },
+            };
+            let err = execute(deps.as_mut(), env.clone(), mock_info("minter", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidVesting {});
+
+            let msg = ExecuteMsg::MintVesting {
+                recipient: "member".to_string(),
+                amount: Uint128::new(500),
+                schedule,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("minter", &[]), msg).unwrap();
+
+            // the unlocked initial balance can still be spent, the minted tokens cannot
+            do_transfer(deps.as_mut(), env.clone(), "member", "shop", 1000).unwrap();
+            let msg = ExecuteMsg::Burn {
+                amount: Uint128::new(1),
+            };
+            let err = execute(deps.as_mut(), env, mock_info("member", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::LockedTokens { locked: Uint128::new(500) });
+        }
+    }
+
+    mod freeze {
+        use super::*;
+        use crate::query::{FrozenAccountsResponse, PausedResponse};
+
+        #[test]
+        fn freeze_blocks_both_directions() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            do_transfer(deps.as_mut(), mock_env(), "alice", "bob", 100).unwrap();
+
+            // only admin or guardian can freeze
+            let msg = ExecuteMsg::Freeze { address: "bob".to_string() };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg.clone()).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            let err = do_transfer(deps.as_mut(), mock_env(), "bob", "alice", 10).unwrap_err();
+            assert_eq!(err, ContractError::AccountFrozen { address: "bob".to_string() });
+            let err = do_transfer(deps.as_mut(), mock_env(), "alice", "bob", 10).unwrap_err();
+            assert_eq!(err, ContractError::AccountFrozen { address: "bob".to_string() });
+            // other accounts are not affected
+            do_transfer(deps.as_mut(), mock_env(), "alice", "carl", 10).unwrap();
+
+            let frozen: FrozenAccountsResponse = from_binary(
+                &query(deps.as_ref(), mock_env(), QueryMsg::FrozenAccounts { start_after: None, limit: None }).unwrap(),
+            )
+            .unwrap();
+            assert_eq!(frozen.accounts, vec!["bob".to_string()]);
+
+            let msg = ExecuteMsg::Unfreeze { address: "bob".to_string() };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            do_transfer(deps.as_mut(), mock_env(), "bob", "alice", 10).unwrap();
+        }
+
+        #[test]
+        fn guardian_pauses_contract() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+
+            // only admin can set the guardian
+            let msg = ExecuteMsg::UpdateGuardian { guardian: Some("guardian".to_string()) };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg.clone()).unwrap_err();
+            assert!(matches!(err, ContractError::Admin(_)));
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), ExecuteMsg::Pause {}).unwrap();
+            let paused: PausedResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Paused {}).unwrap()).unwrap();
+            assert!(paused.paused);
+
+            let err = do_transfer(deps.as_mut(), mock_env(), "alice", "bob", 10).unwrap_err();
+            assert_eq!(err, ContractError::Paused {});
+            let msg = ExecuteMsg::Burn { amount: Uint128::new(10) };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::Paused {});
+
+            execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), ExecuteMsg::Unpause {}).unwrap();
+            do_transfer(deps.as_mut(), mock_env(), "alice", "bob", 10).unwrap();
+        }
+    }
+
+    mod savings {
+        use super::*;
+        use crate::query::LocksResponse;
+        use cw_utils::Duration;
+
+        fn enable_savings(deps: DepsMut, tax_level: u128) {
+            let msg = ExecuteMsg::UpdateSavingsConfig {
+                tax_level,
+                unbonding_period: Duration::Time(100),
+            };
+            execute(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        }
+
+        fn query_locks(deps: Deps, env: Env, address: &str) -> LocksResponse {
+            let msg = QueryMsg::Locks { address: address.to_string() };
+            from_binary(&query(deps, env, msg).unwrap()).unwrap()
+        }
+
+        #[test]
+        fn lock_unlock_and_claim() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+
+            let lock = ExecuteMsg::Lock {
+                amount: Uint128::new(400),
+                duration: Duration::Time(60),
+            };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), lock.clone()).unwrap_err();
+            assert_eq!(err, ContractError::SavingsDisabled {});
+
+            enable_savings(deps.as_mut(), 0);
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), lock).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "alice"), Uint128::new(600));
+
+            // the term is not over yet
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), ExecuteMsg::Unlock {}).unwrap_err();
+            assert_eq!(err, ContractError::NothingToUnlock {});
+
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(60);
+            let locks = query_locks(deps.as_ref(), env.clone(), "alice");
+            assert_eq!(locks.locks.len(), 1);
+            assert_eq!(locks.locks[0].amount, Uint128::new(400));
+
+            execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), ExecuteMsg::Unlock {}).unwrap();
+            let locks = query_locks(deps.as_ref(), env.clone(), "alice");
+            assert!(locks.locks.is_empty());
+            assert_eq!(locks.claims.len(), 1);
+            assert_eq!(locks.claims[0].amount, Uint128::new(400));
+
+            // still unbonding
+            let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), ExecuteMsg::ClaimUnlocked {}).unwrap_err();
+            assert_eq!(err, ContractError::NothingToClaim {});
+
+            env.block.time = env.block.time.plus_seconds(100);
+            execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), ExecuteMsg::ClaimUnlocked {}).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "alice"), Uint128::new(1000));
+            assert!(query_locks(deps.as_ref(), env, "alice").claims.is_empty());
+        }
+
+        #[test]
+        fn locked_funds_keep_their_worth() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(20_000_000_000));
+            enable_savings(deps.as_mut(), 0);
+
+            let lock = ExecuteMsg::Lock {
+                amount: Uint128::new(10_000_000_000),
+                duration: Duration::Time(60),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), lock).unwrap();
+
+            // after the demurrage modifier decayed, the untaxed lock is worth more balance units
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(6000);
+            let transfer = ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(1),
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), transfer).unwrap();
+            let locks = query_locks(deps.as_ref(), env, "alice");
+            assert!(locks.locks[0].amount > Uint128::new(10_000_000_000));
+        }
+
+        #[test]
+        fn savings_bonus_is_paid_by_the_sink() {
+            let mut deps = mock_dependencies();
+            let locked = Uint128::new(10_000_000_000);
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(20_000_000_000));
+            enable_savings(deps.as_mut(), 0);
+            let lock = ExecuteMsg::Lock {
+                amount: locked,
+                duration: Duration::Time(60),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), lock).unwrap();
+            let supply = query_token_info(deps.as_ref()).unwrap().total_supply;
+            assert_eq!(sum_balances(deps.as_ref()) + locked, supply);
+
+            // closing the periods funds the sink with the demurrage
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(6000);
+            let transfer = ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(1),
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), transfer).unwrap();
+            let sink = get_balance(deps.as_ref(), "sinkaddress");
+            let balances = sum_balances(deps.as_ref());
+
+            // no bonus moves out of a frozen sink
+            let freeze = ExecuteMsg::Freeze { address: "sinkaddress".to_string() };
+            execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), freeze).unwrap();
+            let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), ExecuteMsg::Unlock {}).unwrap_err();
+            assert_eq!(err, ContractError::AccountFrozen { address: "sinkaddress".to_string() });
+            let unfreeze = ExecuteMsg::Unfreeze { address: "sinkaddress".to_string() };
+            execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), unfreeze).unwrap();
+
+            execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), ExecuteMsg::Unlock {}).unwrap();
+            let claimed = query_locks(deps.as_ref(), env.clone(), "alice").claims[0].amount;
+            let bonus = claimed - locked;
+            assert!(!bonus.is_zero());
+            assert_eq!(get_balance(deps.as_ref(), "sinkaddress"), sink - bonus);
+            assert_eq!(sum_balances(deps.as_ref()) + claimed, balances + locked);
+
+            // the claim pays out exactly what was unlocked
+            let alice = get_balance(deps.as_ref(), "alice");
+            env.block.time = env.block.time.plus_seconds(100);
+            execute(deps.as_mut(), env, mock_info("alice", &[]), ExecuteMsg::ClaimUnlocked {}).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "alice"), alice + claimed);
+            assert_eq!(sum_balances(deps.as_ref()), balances + locked);
+            assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, supply);
+        }
+
+        #[test]
+        fn savings_tax_cannot_exceed_demurrage() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+
+            let msg = ExecuteMsg::UpdateSavingsConfig {
+                tax_level: 501059083733730001,
+                unbonding_period: Duration::Time(100),
+            };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidSavingsTax {});
+        }
+    }
+
+    mod projection {
+        use super::*;
+        use crate::query::{ProjectedBalanceResponse, TaxDueResponse};
+
+        #[test]
+        fn projects_demurrage_without_changing_state() {
+            let mut deps = mock_dependencies();
+            let amount = Uint128::new(20_000_000_000);
+            do_instantiate(deps.as_mut(), "alice", amount);
+
+            let env = mock_env();
+            let at = env.block.time.plus_seconds(6000);
+            let msg = QueryMsg::ProjectedBalance { address: "alice".to_string(), at };
+            let projected: ProjectedBalanceResponse =
+                from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+            assert!(projected.balance < amount);
+
+            let msg = QueryMsg::TaxDue { address: "alice".to_string(), until: at };
+            let due: TaxDueResponse = from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+            assert_eq!(due.tax_due, amount - projected.balance);
+
+            // nothing was applied by the queries
+            assert_eq!(query_demurrage_amount(deps.as_ref()).unwrap().demurrage_amount.u128(), 100000000000000000000000);
+
+            // the projection matches the modifier cached once that time is reached
+            let mut later = env.clone();
+            later.block.time = at;
+            let msg = ExecuteMsg::Transfer { recipient: "bob".to_string(), amount: Uint128::new(1) };
+            execute(deps.as_mut(), later.clone(), mock_info("alice", &[]), msg).unwrap();
+            let demurrage_amount = query_demurrage_amount(deps.as_ref()).unwrap().demurrage_amount;
+            assert_eq!(
+                from_base_amount(amount, demurrage_amount.u128()),
+                projected.balance
+            );
+
+            // the past cannot be projected
+            let msg = QueryMsg::TaxDue { address: "alice".to_string(), until: env.block.time };
+            query(deps.as_ref(), later, msg).unwrap_err();
+        }
+    }
+
+    mod balances {
+        use super::*;
+        use crate::query::{AccountBalance, BalancesResponse};
+
+        fn balance(address: &str, amount: u128) -> AccountBalance {
+            AccountBalance {
+                address: address.to_string(),
+                balance: Uint128::new(amount),
+            }
+        }
+
+        #[test]
+        fn balances_sorted_by_amount() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            for (recipient, amount) in [("bob", 300u128), ("carl", 500)] {
+                let msg = ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount: Uint128::new(amount),
+                };
+                execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+            }
+
+            // the index follows the balance changes
+            let msg = QueryMsg::TopHolders { limit: Some(3) };
+            let top: BalancesResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(
+                top.balances,
+                vec![balance("carl", 500), balance("bob", 300), balance("alice", 200)]
+            );
+
+            let msg = QueryMsg::AllBalances { start_after: None, limit: Some(2) };
+            let page: BalancesResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(page.balances, vec![balance("alice", 200), balance("bob", 300)]);
+
+            let msg = QueryMsg::AllBalances { start_after: Some("bob".to_string()), limit: Some(1) };
+            let page: BalancesResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(page.balances, vec![balance("carl", 500)]);
+        }
+
+        #[test]
+        fn top_holders_by_worth() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(20_000_000_000));
+            for (recipient, amount) in [("bob", 9_900_000_000u128), ("carl", 10_000_000_000)] {
+                let msg = ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount: Uint128::new(amount),
+                };
+                execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+            }
+            let msg = ExecuteMsg::UpdateClass { class: "luxury".to_string(), tax_level: 1_000_000_000_000_000_000_000_000 };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let msg = ExecuteMsg::AssignClass { address: "carl".to_string(), class: Some("luxury".to_string()) };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            // carl moves to the class rate with his next balance change
+            let msg = ExecuteMsg::Transfer {
+                recipient: "dave".to_string(),
+                amount: Uint128::new(1),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("carl", &[]), msg).unwrap();
+
+            // carl stores more but is worth less once his class rate took its share
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(60_000);
+            let msg = QueryMsg::TopHolders { limit: Some(2) };
+            let top: BalancesResponse = from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+            assert_eq!(top.balances[0].address, "bob");
+            assert_eq!(top.balances[1].address, "carl");
+            assert!(top.balances[1].balance < Uint128::new(9_899_999_999));
+
+            let msg = QueryMsg::AllBalances { start_after: Some("alice".to_string()), limit: Some(2) };
+            let page: BalancesResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+            assert_eq!(page.balances, top.balances);
+        }
+    }
+
+    mod sweep {
+        use super::*;
+        use crate::state::{LAST_ACTIVITY, SPENDING};
+
+        #[test]
+        fn sweeps_dormant_dust() {
+            let mut deps = mock_dependencies();
+            // 3 decimals, so anything worth less than 500 rounds to zero tokens
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            do_transfer(deps.as_mut(), mock_env(), "alice", "bob", 700).unwrap();
+            do_transfer(deps.as_mut(), mock_env(), "bob", "carl", 1).unwrap();
+
+            // alice holds dust but just spent, bob holds more than dust
+            let msg = ExecuteMsg::SweepDust { start_after: None, limit: None };
+            let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg.clone()).unwrap();
+            assert_eq!(res.events.len(), 1);
+            assert_eq!(res.events[0].ty, "dust_swept");
+            assert_eq!(get_balance(deps.as_ref(), "carl"), Uint128::zero());
+            assert_eq!(get_balance(deps.as_ref(), "alice"), Uint128::new(300));
+            assert_eq!(get_balance(deps.as_ref(), "bob"), Uint128::new(699));
+            assert_eq!(get_balance(deps.as_ref(), "sinkaddress"), Uint128::new(1));
+            let accounts = query_all_accounts(deps.as_ref(), None, None).unwrap().accounts;
+            assert!(!accounts.contains(&"carl".to_string()));
+            // nothing is kept about the swept account
+            let carl = Addr::unchecked("carl");
+            assert!(!ACCOUNT_STATS.has(&deps.storage, &carl));
+            assert_eq!(TWAB.prefix(&carl).range(&deps.storage, None, None, Order::Ascending).count(), 0);
+
+            // once alice is dormant, her dust is swept as well
+            let alice = Addr::unchecked("alice");
+            let spending = |storage: &dyn Storage| SPENDING.prefix(&alice).range(storage, None, None, Order::Ascending).count();
+            assert_eq!(spending(&deps.storage), 1);
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(30 * 24 * 60 * 60);
+            execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "alice"), Uint128::zero());
+            assert!(get_balance(deps.as_ref(), "bob") > Uint128::zero());
+            assert!(!ACCOUNT_STATS.has(&deps.storage, &alice));
+            assert!(!LAST_ACTIVITY.has(&deps.storage, &alice));
+            assert_eq!(spending(&deps.storage), 0);
+        }
+
+        #[test]
+        fn scan_is_bounded_and_continues_from_cursor() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            do_transfer(deps.as_mut(), mock_env(), "alice", "bob", 700).unwrap();
+            do_transfer(deps.as_mut(), mock_env(), "bob", "carl", 1).unwrap();
+            do_transfer(deps.as_mut(), mock_env(), "bob", "dave", 2).unwrap();
+            do_transfer(deps.as_mut(), mock_env(), "bob", "erin", 3).unwrap();
+            let msg = ExecuteMsg::Freeze { address: "carl".to_string() };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            // the empty sink and carl are kept and dave swept, erin is left for the next call
+            let msg = ExecuteMsg::SweepDust { start_after: None, limit: Some(3) };
+            let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+            assert!(res.attributes.contains(&attr("accounts", "1")));
+            assert!(res.attributes.contains(&attr("start_after", "carl")));
+            assert_eq!(get_balance(deps.as_ref(), "dave"), Uint128::zero());
+            assert_eq!(get_balance(deps.as_ref(), "erin"), Uint128::new(3));
+
+            // the sink now holds dave's dust, erin is swept, alice just spent and the scan ends at bob
+            let msg = ExecuteMsg::SweepDust { start_after: Some("carl".to_string()), limit: Some(3) };
+            let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+            assert!(res.attributes.contains(&attr("accounts", "1")));
+            assert!(!res.attributes.iter().any(|attr| attr.key == "start_after"));
+            assert_eq!(get_balance(deps.as_ref(), "erin"), Uint128::zero());
+            assert_eq!(get_balance(deps.as_ref(), "carl"), Uint128::new(1));
+            assert_eq!(get_balance(deps.as_ref(), "alice"), Uint128::new(300));
+            assert_eq!(get_balance(deps.as_ref(), "sinkaddress"), Uint128::new(5));
+        }
+    }
+
+    mod stats {
+        use super::*;
+        use crate::query::{AccountStatsResponse, PeriodMetricsResponse};
+
+        fn query_stats(deps: Deps, env: Env, address: &str) -> AccountStatsResponse {
+            let msg = QueryMsg::AccountStats { address: address.to_string() };
+            from_binary(&query(deps, env, msg).unwrap()).unwrap()
+        }
+
+        #[test]
+        fn tracks_transfers_and_tax() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(20_000_000_000));
+            let env = mock_env();
+
+            let msg = ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(5_000_000_000),
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), msg).unwrap();
+
+            let alice = query_stats(deps.as_ref(), env.clone(), "alice");
+            assert_eq!(alice.sent, Uint128::new(5_000_000_000));
+            assert_eq!(alice.received, Uint128::zero());
+            assert_eq!(alice.tax_paid, Uint128::zero());
+            assert_eq!(alice.last_spend, Some(env.block.time));
+
+            let bob = query_stats(deps.as_ref(), env.clone(), "bob");
+            assert_eq!(bob.received, Uint128::new(5_000_000_000));
+            assert_eq!(bob.last_spend, None);
+
+            // demurrage accrues on the held balance
+            let mut later = env;
+            later.block.time = later.block.time.plus_seconds(6000);
+            let alice = query_stats(deps.as_ref(), later, "alice");
+            assert!(alice.tax_paid > Uint128::zero());
+            assert_eq!(query_stats(deps.as_ref(), mock_env(), "carl"), AccountStatsResponse::default());
+        }
+
+        #[test]
+        fn initial_holders_pay_tax_from_genesis() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(20_000_000_000));
+
+            let mut later = mock_env();
+            later.block.time = later.block.time.plus_seconds(6000);
+            let accrued = query_stats(deps.as_ref(), later.clone(), "alice").tax_paid;
+            assert!(accrued > Uint128::zero());
+
+            // the first change keeps what accrued since instantiation
+            let msg = ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(1),
+            };
+            execute(deps.as_mut(), later, mock_info("alice", &[]), msg).unwrap();
+            assert!(STATE.load(&deps.storage).unwrap().current_period > 0);
+            let alice = ACCOUNT_STATS.load(&deps.storage, &Addr::unchecked("alice")).unwrap();
+            assert_eq!(alice.tax_paid, accrued);
+        }
+
+        #[test]
+        fn period_metrics_roll_over() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let transfer = |deps: DepsMut, env: Env, to: &str, amount: u128| {
+                let msg = ExecuteMsg::Transfer {
+                    recipient: to.to_string(),
+                    amount: Uint128::new(amount),
+                };
+                execute(deps, env, mock_info("alice", &[]), msg).unwrap();
+            };
+
+            transfer(deps.as_mut(), mock_env(), "bob", 100);
+            transfer(deps.as_mut(), mock_env(), "carl", 50);
+            transfer(deps.as_mut(), mock_env(), "carl", 50);
+            let period = STATE.load(&deps.storage).unwrap().current_period;
+
+            let msg = QueryMsg::PeriodMetrics { period };
+            let metrics: PeriodMetricsResponse = from_binary(&query(deps.as_ref(), mock_env(), msg.clone()).unwrap()).unwrap();
+            assert_eq!(
+                metrics,
+                PeriodMetricsResponse {
+                    period,
+                    transfers: 3,
+                    volume: Uint128::new(200),
+                    unique_senders: 1,
+                    unique_receivers: 2,
+                }
+            );
+
+            // the next period starts counting from zero
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(60);
+            transfer(deps.as_mut(), env, "bob", 10);
+            assert_eq!(STATE.load(&deps.storage).unwrap().current_period, period + 1);
+            let next: PeriodMetricsResponse = from_binary(
+                &query(deps.as_ref(), mock_env(), QueryMsg::PeriodMetrics { period: period + 1 }).unwrap(),
+            )
+            .unwrap();
+            assert_eq!(next.transfers, 1);
+            assert_eq!(next.unique_receivers, 1);
+            let old: PeriodMetricsResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(old, metrics);
+        }
+    }
+
+    mod tiers {
+        use super::*;
+        use crate::query::{AccountStatsResponse, ProjectedBalanceResponse, TaxTiersResponse};
+        use crate::state::TaxTier;
+
+        fn tier(up_to: Option<u128>, tax_level: u128) -> TaxTier {
+            TaxTier {
+                up_to: up_to.map(Uint128::new),
+                tax_level,
+            }
+        }
+
+        fn tax_paid(deps: Deps, env: Env) -> Uint128 {
+            let msg = QueryMsg::AccountStats { address: "alice".to_string() };
+            let stats: AccountStatsResponse = from_binary(&query(deps, env, msg).unwrap()).unwrap();
+            stats.tax_paid
+        }
+
+        #[test]
+        fn tiers_must_be_contiguous_and_increasing() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+
+            let invalid = vec![
+                // last tier must be unbounded
+                vec![tier(Some(100), 0), tier(Some(200), 10)],
+                // bounds must increase
+                vec![tier(Some(100), 0), tier(Some(100), 10), tier(None, 20)],
+                // rates must not decrease
+                vec![tier(Some(100), 10), tier(None, 0)],
+            ];
+            for tiers in invalid {
+                let msg = ExecuteMsg::UpdateTaxTiers { tiers };
+                let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+                assert_eq!(err, ContractError::InvalidTaxTiers {});
+            }
+
+            let tiers = vec![tier(Some(100), 0), tier(Some(1000), 10), tier(None, 30)];
+            let msg = ExecuteMsg::UpdateTaxTiers { tiers: tiers.clone() };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg.clone()).unwrap_err();
+            assert!(matches!(err, ContractError::Admin(_)));
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            let res: TaxTiersResponse = from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::TaxTiers {}).unwrap()).unwrap();
+            assert_eq!(res.tiers, tiers);
+
+            // an empty list goes back to the flat rate
+            let msg = ExecuteMsg::UpdateTaxTiers { tiers: vec![] };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let res: TaxTiersResponse = from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::TaxTiers {}).unwrap()).unwrap();
+            assert!(res.tiers.is_empty());
+        }
+
+        #[test]
+        fn tax_free_tier_lowers_demurrage() {
+            let amount = Uint128::new(20_000_000_000);
+            let mut later = mock_env();
+            later.block.time = later.block.time.plus_seconds(6000);
+
+            // the same history with the flat rate and with a tax free first half
+            let mut flat = mock_dependencies();
+            do_instantiate(flat.as_mut(), "alice", amount);
+            do_transfer(flat.as_mut(), mock_env(), "alice", "bob", 1).unwrap();
+            do_transfer(flat.as_mut(), later.clone(), "alice", "bob", 1).unwrap();
+
+            let mut tiered = mock_dependencies();
+            do_instantiate(tiered.as_mut(), "alice", amount);
+            let tiers = vec![tier(Some(10_000_000_000), 0), tier(None, 501059083733730000)];
+            let msg = ExecuteMsg::UpdateTaxTiers { tiers };
+            execute(tiered.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            do_transfer(tiered.as_mut(), mock_env(), "alice", "bob", 1).unwrap();
+
+            let msg = QueryMsg::ProjectedBalance { address: "alice".to_string(), at: later.block.time };
+            let projected: ProjectedBalanceResponse =
+                from_binary(&query(tiered.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            let pending = tax_paid(tiered.as_ref(), later.clone());
+            do_transfer(tiered.as_mut(), later.clone(), "alice", "bob", 1).unwrap();
+
+            let flat_tax = tax_paid(flat.as_ref(), later.clone());
+            let tiered_tax = tax_paid(tiered.as_ref(), later.clone());
+            assert!(tiered_tax > Uint128::zero());
+            assert!(tiered_tax < flat_tax);
+            assert_eq!(tiered_tax, pending);
+            // the tax not taken stays on the balance
+            assert!(get_balance(tiered.as_ref(), "alice") > get_balance(flat.as_ref(), "alice"));
+            assert_eq!(projected.balance, amount - Uint128::new(1) - tiered_tax);
+        }
+
+        #[test]
+        fn settlement_moves_tax_between_holder_and_sink() {
+            let amount = Uint128::new(20_000_000_000);
+            let mut later = mock_env();
+            later.block.time = later.block.time.plus_seconds(6000);
+
+            // the refund of a tax free tier is paid by the sink
+            let mut flat = mock_dependencies();
+            do_instantiate(flat.as_mut(), "alice", amount);
+            do_transfer(flat.as_mut(), mock_env(), "alice", "bob", 1).unwrap();
+            do_transfer(flat.as_mut(), later.clone(), "alice", "bob", 1).unwrap();
+
+            let mut tiered = mock_dependencies();
+            do_instantiate(tiered.as_mut(), "alice", amount);
+            let tiers = vec![tier(Some(10_000_000_000), 0), tier(None, 501059083733730000)];
+            let msg = ExecuteMsg::UpdateTaxTiers { tiers };
+            execute(tiered.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            do_transfer(tiered.as_mut(), mock_env(), "alice", "bob", 1).unwrap();
+            do_transfer(tiered.as_mut(), later.clone(), "alice", "bob", 1).unwrap();
+
+            assert!(get_balance(tiered.as_ref(), "sinkaddress") < get_balance(flat.as_ref(), "sinkaddress"));
+            assert_eq!(sum_balances(tiered.as_ref()), sum_balances(flat.as_ref()));
+            assert_eq!(query_token_info(tiered.as_ref()).unwrap().total_supply, amount);
+
+            // without flat demurrage, the tiered tax is collected by the sink
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", amount);
+            let msg = ExecuteMsg::ChangeTaxLevel { amount: 0 };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let msg = ExecuteMsg::UpdateTaxTiers { tiers: vec![tier(None, 501059083733730000)] };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            do_transfer(deps.as_mut(), mock_env(), "alice", "bob", 1).unwrap();
+            do_transfer(deps.as_mut(), later.clone(), "alice", "bob", 1).unwrap();
+
+            assert!(get_balance(deps.as_ref(), "sinkaddress") > Uint128::zero());
+            let msg = QueryMsg::AccountStats { address: "bob".to_string() };
+            let bob: AccountStatsResponse = from_binary(&query(deps.as_ref(), later.clone(), msg).unwrap()).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "sinkaddress"), tax_paid(deps.as_ref(), later) + bob.tax_paid);
+            assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, sum_balances(deps.as_ref()));
+        }
+    }
+
+    mod classes {
+        use super::*;
+        use crate::query::{AccountsByClassResponse, ProjectedBalanceResponse};
+
+        fn admin(deps: DepsMut, msg: ExecuteMsg) -> Result<Response, ContractError> {
+            execute(deps, mock_env(), mock_info("creator", &[]), msg)
+        }
+
+        fn assign(deps: DepsMut, address: &str, class: &str) -> Result<Response, ContractError> {
+            let msg = ExecuteMsg::AssignClass {
+                address: address.to_string(),
+                class: Some(class.to_string()),
+            };
+            admin(deps, msg)
+        }
+
+        fn projected(deps: Deps, address: &str, at: Timestamp) -> Uint128 {
+            let msg = QueryMsg::ProjectedBalance { address: address.to_string(), at };
+            let res: ProjectedBalanceResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.balance
+        }
+
+        #[test]
+        fn class_rate_replaces_flat_tax() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(20_000_000_000));
+            do_transfer(deps.as_mut(), mock_env(), "alice", "bob", 10_000_000_000).unwrap();
+
+            let err = assign(deps.as_mut(), "alice", "ngo").unwrap_err();
+            assert_eq!(err, ContractError::ClassNotFound {});
+            let msg = ExecuteMsg::UpdateClass { class: "ngo".to_string(), tax_level: 0 };
+            admin(deps.as_mut(), msg).unwrap();
+            assign(deps.as_mut(), "alice", "ngo").unwrap();
+
+            // the class rate applies from the next balance change of the member
+            do_transfer(deps.as_mut(), mock_env(), "alice", "carl", 1).unwrap();
+            let at = mock_env().block.time.plus_seconds(6000);
+            assert_eq!(projected(deps.as_ref(), "alice", at), Uint128::new(9_999_999_999));
+            assert!(projected(deps.as_ref(), "bob", at) < Uint128::new(10_000_000_000));
+        }
+
+        #[test]
+        fn tax_free_class_is_refunded_by_the_sink() {
+            let amount = Uint128::new(20_000_000_000);
+            let mut later = mock_env();
+            later.block.time = later.block.time.plus_seconds(6000);
+            let msg = ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(1),
+            };
+
+            let mut flat = mock_dependencies();
+            do_instantiate(flat.as_mut(), "alice", amount);
+            do_transfer(flat.as_mut(), mock_env(), "alice", "bob", 1).unwrap();
+            execute(flat.as_mut(), later.clone(), mock_info("alice", &[]), msg.clone()).unwrap();
+
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", amount);
+            let class = ExecuteMsg::UpdateClass { class: "ngo".to_string(), tax_level: 0 };
+            admin(deps.as_mut(), class).unwrap();
+            assign(deps.as_mut(), "alice", "ngo").unwrap();
+            do_transfer(deps.as_mut(), mock_env(), "alice", "bob", 1).unwrap();
+            execute(deps.as_mut(), later, mock_info("alice", &[]), msg).unwrap();
+
+            // alice kept her worth, paid for by the sink rather than new supply
+            assert!(get_balance(deps.as_ref(), "alice") > get_balance(flat.as_ref(), "alice"));
+            assert!(get_balance(deps.as_ref(), "sinkaddress") < get_balance(flat.as_ref(), "sinkaddress"));
+            assert_eq!(sum_balances(deps.as_ref()), sum_balances(flat.as_ref()));
+            assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, amount);
+        }
+
+        #[test]
+        fn accounts_by_class() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            for class in ["merchant", "consumer"] {
+                let msg = ExecuteMsg::UpdateClass { class: class.to_string(), tax_level: 10 };
+                admin(deps.as_mut(), msg).unwrap();
+            }
+            assign(deps.as_mut(), "bob", "merchant").unwrap();
+            assign(deps.as_mut(), "carl", "merchant").unwrap();
+            assign(deps.as_mut(), "alice", "consumer").unwrap();
+            // reassigning moves the account to the other class
+            assign(deps.as_mut(), "alice", "merchant").unwrap();
+            let msg = ExecuteMsg::AssignClass { address: "carl".to_string(), class: None };
+            admin(deps.as_mut(), msg).unwrap();
+
+            let query_class = |deps: Deps, class: &str, start_after: Option<&str>| {
+                let msg = QueryMsg::AccountsByClass {
+                    class: class.to_string(),
+                    start_after: start_after.map(String::from),
+                    limit: Some(1),
+                };
+                let res: AccountsByClassResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+                res.accounts
+            };
+            assert_eq!(query_class(deps.as_ref(), "merchant", None), vec!["alice".to_string()]);
+            assert_eq!(query_class(deps.as_ref(), "merchant", Some("alice")), vec!["bob".to_string()]);
+            assert!(query_class(deps.as_ref(), "merchant", Some("bob")).is_empty());
+            assert!(query_class(deps.as_ref(), "consumer", None).is_empty());
+        }
+    }
+
+    mod exempt_floor {
+        use super::*;
+        use crate::query::{ProjectedBalanceResponse, TaxableBalanceResponse};
+
+        #[test]
+        fn only_balance_above_floor_decays() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(20_000_000_000));
+            let floor = Uint128::new(10_000_000_000);
+
+            let msg = ExecuteMsg::SetExemptFloor { amount: floor };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg.clone()).unwrap_err();
+            assert!(matches!(err, ContractError::Admin(_)));
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            let msg = ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(5_000_000_000),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+
+            let msg = QueryMsg::TaxableBalance { address: "alice".to_string() };
+            let taxable: TaxableBalanceResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(
+                taxable,
+                TaxableBalanceResponse {
+                    balance: Uint128::new(15_000_000_000),
+                    exempt: floor,
+                    taxable: Uint128::new(5_000_000_000),
+                }
+            );
+
+            let at = mock_env().block.time.plus_seconds(6000);
+            let projected = |deps: Deps, address: &str| {
+                let msg = QueryMsg::ProjectedBalance { address: address.to_string(), at };
+                let res: ProjectedBalanceResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+                res.balance
+            };
+            // bob stays below the floor, alice only loses on the part above it
+            assert_eq!(projected(deps.as_ref(), "bob"), Uint128::new(5_000_000_000));
+            let alice = projected(deps.as_ref(), "alice");
+            assert!(alice < Uint128::new(15_000_000_000));
+            assert!(alice > floor);
+        }
+
+        #[test]
+        fn floor_is_refunded_by_the_sink() {
+            let amount = Uint128::new(20_000_000_000);
+            let mut later = mock_env();
+            later.block.time = later.block.time.plus_seconds(6000);
+            let transfer = |deps: DepsMut, env: Env| {
+                let msg = ExecuteMsg::Transfer {
+                    recipient: "bob".to_string(),
+                    amount: Uint128::new(1),
+                };
+                execute(deps, env, mock_info("alice", &[]), msg).unwrap();
+            };
+
+            let mut flat = mock_dependencies();
+            do_instantiate(flat.as_mut(), "alice", amount);
+            transfer(flat.as_mut(), mock_env());
+            transfer(flat.as_mut(), later.clone());
+
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", amount);
+            let msg = ExecuteMsg::SetExemptFloor { amount: Uint128::new(10_000_000_000) };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            transfer(deps.as_mut(), mock_env());
+            transfer(deps.as_mut(), later);
+
+            assert!(get_balance(deps.as_ref(), "alice") > get_balance(flat.as_ref(), "alice"));
+            assert!(get_balance(deps.as_ref(), "sinkaddress") < get_balance(flat.as_ref(), "sinkaddress"));
+            assert_eq!(sum_balances(deps.as_ref()), sum_balances(flat.as_ref()));
+            assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, amount);
+        }
+    }
+
+    mod rebates {
+        use super::*;
+        use crate::query::PendingRebateResponse;
+        use crate::state::{REBATE_CLAIMED, REBATE_POOLS};
+        use cosmwasm_std::Decimal;
+
+        fn pending(deps: Deps, address: &str) -> PendingRebateResponse {
+            let msg = QueryMsg::PendingRebate { address: address.to_string() };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        }
+
+        #[test]
+        fn rebates_follow_spending() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(20_000_000_000));
+            let msg = ExecuteMsg::SetRebateShare { share: Decimal::percent(50) };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            do_transfer(deps.as_mut(), mock_env(), "alice", "bob", 3_000_000_000).unwrap();
+            do_transfer(deps.as_mut(), mock_env(), "bob", "carl", 1_000_000_000).unwrap();
+            let period = STATE.load(&deps.storage).unwrap().current_period;
+
+            // the period is still open
+            let msg = ExecuteMsg::ClaimRebate { period };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg.clone()).unwrap_err();
+            assert_eq!(err, ContractError::NoRebate {});
+
+            // a day later enough demurrage was collected for a visible pool
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(24 * 60 * 60);
+            do_transfer(deps.as_mut(), env.clone(), "carl", "alice", 1).unwrap();
+
+            let alice = pending(deps.as_ref(), "alice");
+            let bob = pending(deps.as_ref(), "bob");
+            assert_eq!(alice.rebates.len(), 1);
+            assert_eq!(alice.rebates[0].period, period);
+            assert!(!bob.total.is_zero());
+            // alice spent three times as much as bob
+            assert!(alice.total > bob.total * Uint128::new(2));
+            assert!(alice.total < bob.total * Uint128::new(4));
+            // carl only spent in the new period
+            assert!(pending(deps.as_ref(), "carl").rebates.is_empty());
+
+            // the pool holds worth, the claim credits its balance units
+            let alice_addr = Addr::unchecked("alice");
+            let before = balances().load(&deps.storage, &alice_addr).unwrap();
+            execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), msg.clone()).unwrap();
+            let demurrage_amount = STATE.load(&deps.storage).unwrap().demurrage_amount;
+            let credited = Uint128::from(to_base_amount(alice.total.u128(), demurrage_amount));
+            assert_eq!(balances().load(&deps.storage, &alice_addr).unwrap(), before + credited);
+            assert!(pending(deps.as_ref(), "alice").rebates.is_empty());
+            let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), msg.clone()).unwrap_err();
+            assert_eq!(err, ContractError::NoRebate {});
+
+            // the claims are booked against the pool
+            execute(deps.as_mut(), env, mock_info("bob", &[]), msg).unwrap();
+            let claimed = REBATE_CLAIMED.load(&deps.storage, period).unwrap();
+            assert_eq!(claimed, alice.total + bob.total);
+            assert!(claimed <= REBATE_POOLS.load(&deps.storage, period).unwrap());
+        }
+    }
+
+    mod twab {
+        use super::*;
+        use crate::projection::projected_demurrage_amount;
+        use crate::query::TwabBalanceResponse;
+
+        fn twab(deps: Deps, env: Env, address: &str, from: Timestamp, to: Timestamp) -> StdResult<Uint128> {
+            let msg = QueryMsg::TwabBalance { address: address.to_string(), from, to };
+            let res: TwabBalanceResponse = from_binary(&query(deps, env, msg)?)?;
+            Ok(res.balance)
+        }
+
+        #[test]
+        fn time_weighted_average() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let start = mock_env().block.time;
+
+            let mut env = mock_env();
+            env.block.time = start.plus_seconds(100);
+            let msg = ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(500),
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), msg).unwrap();
+
+            env.block.time = start.plus_seconds(200);
+            let end = env.block.time;
+            // the worth decays a little after the change
+            assert_eq!(twab(deps.as_ref(), env.clone(), "alice", start, end).unwrap(), Uint128::new(749));
+            assert_eq!(twab(deps.as_ref(), env.clone(), "bob", start, end).unwrap(), Uint128::new(249));
+            let half = start.plus_seconds(100);
+            assert_eq!(twab(deps.as_ref(), env.clone(), "alice", start, half).unwrap(), Uint128::new(1000));
+            assert_eq!(twab(deps.as_ref(), env.clone(), "alice", half, end).unwrap(), Uint128::new(499));
+            // a window crossing the first change, before bob held anything
+            let quarter = start.plus_seconds(50);
+            assert_eq!(twab(deps.as_ref(), env.clone(), "alice", quarter, end).unwrap(), Uint128::new(666));
+            assert_eq!(twab(deps.as_ref(), env.clone(), "bob", quarter, end).unwrap(), Uint128::new(333));
+
+            // the window cannot reach into the future
+            twab(deps.as_ref(), env, "alice", start, end.plus_seconds(1)).unwrap_err();
+        }
+
+        #[test]
+        fn holders_without_changes() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let start = mock_env().block.time;
+            let mut env = mock_env();
+            env.block.time = start.plus_seconds(200);
+            let (from, to) = (start.plus_seconds(50), start.plus_seconds(150));
+
+            // alice never transacted, her initial balance counts from the start
+            assert_eq!(twab(deps.as_ref(), env.clone(), "alice", from, to).unwrap(), Uint128::new(1000));
+            assert_eq!(twab(deps.as_ref(), env.clone(), "bob", from, to).unwrap(), Uint128::zero());
+
+            // an account without any point holds its balance since the start
+            let alice = Addr::unchecked("alice");
+            TWAB.remove(deps.as_mut().storage, (&alice, start.seconds()));
+            assert_eq!(twab(deps.as_ref(), env.clone(), "alice", from, to).unwrap(), Uint128::new(1000));
+
+            // and from its first point, the balance before it is what it accumulated
+            env.block.time = start.plus_seconds(100);
+            let msg = ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(500),
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), msg).unwrap();
+            env.block.time = start.plus_seconds(200);
+            let (from, to) = (start.plus_seconds(50), start.plus_seconds(200));
+            assert_eq!(twab(deps.as_ref(), env, "alice", from, to).unwrap(), Uint128::new(666));
+        }
+
+        #[test]
+        fn decays_over_quiet_periods() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1_000_000));
+            let mut state = STATE.load(&deps.storage).unwrap();
+            state.tax_level = 1_000_000_000_000_000_000_000_000;
+            STATE.save(deps.as_mut().storage, &state).unwrap();
+            let start = mock_env().block.time;
+
+            // ten periods pass without any transfer
+            let mut env = mock_env();
+            env.block.time = start.plus_seconds(600);
+            let worth = from_base_amount(
+                get_balance(deps.as_ref(), "alice"),
+                projected_demurrage_amount(&state, env.block.time),
+            );
+            assert!(worth < Uint128::new(1_000_000));
+
+            let average = twab(deps.as_ref(), env.clone(), "alice", start, env.block.time).unwrap();
+            assert!(average < Uint128::new(1_000_000));
+            assert!(average > worth);
+            let first_half = twab(deps.as_ref(), env.clone(), "alice", start, start.plus_seconds(300)).unwrap();
+            let second_half = twab(deps.as_ref(), env, "alice", start.plus_seconds(300), start.plus_seconds(600)).unwrap();
+            assert!(second_half < first_half);
+        }
+    }
+
+    mod ratelimit {
+        use super::*;
+        use crate::query::RateLimitResponse;
+
+        fn query_limit(deps: Deps, env: Env, address: &str) -> RateLimitResponse {
+            let msg = QueryMsg::RateLimit { address: address.to_string() };
+            from_binary(&query(deps, env, msg).unwrap()).unwrap()
+        }
+
+        #[test]
+        fn account_limit_resets_each_period() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let msg = ExecuteMsg::SetRateLimit {
+                address: "alice".to_string(),
+                limit: Some(Uint128::new(100)),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            do_transfer(deps.as_mut(), mock_env(), "alice", "shop", 60).unwrap();
+            let err = do_transfer(deps.as_mut(), mock_env(), "alice", "shop", 50).unwrap_err();
+            assert_eq!(err, ContractError::RateLimited { remaining: Uint128::new(40) });
+            let usage = query_limit(deps.as_ref(), mock_env(), "alice");
+            assert_eq!(usage.limit, Some(Uint128::new(100)));
+            assert_eq!(usage.used, Uint128::new(60));
+
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(120);
+            do_transfer(deps.as_mut(), env.clone(), "alice", "shop", 50).unwrap();
+            assert_eq!(query_limit(deps.as_ref(), env, "alice").used, Uint128::new(50));
+        }
+
+        #[test]
+        fn global_cap_covers_all_accounts() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let msg = ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(500),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+
+            let msg = ExecuteMsg::SetGlobalRateLimit { limit: Some(Uint128::new(100)) };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg.clone()).unwrap_err();
+            assert!(matches!(err, ContractError::Admin(_)));
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            do_transfer(deps.as_mut(), mock_env(), "alice", "shop", 70).unwrap();
+            let err = do_transfer(deps.as_mut(), mock_env(), "bob", "shop", 40).unwrap_err();
+            assert_eq!(err, ContractError::RateLimited { remaining: Uint128::new(30) });
+            do_transfer(deps.as_mut(), mock_env(), "bob", "shop", 30).unwrap();
+
+            let usage = query_limit(deps.as_ref(), mock_env(), "bob");
+            assert_eq!(usage.limit, None);
+            assert_eq!(usage.global_used, Uint128::new(100));
+
+            // a new period starts from zero
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(120);
+            do_transfer(deps.as_mut(), env.clone(), "bob", "shop", 40).unwrap();
+            assert_eq!(query_limit(deps.as_ref(), env, "bob").global_used, Uint128::new(40));
+        }
+
+        #[test]
+        fn idle_time_does_not_reset_limits_within_a_block() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let msg = ExecuteMsg::SetRateLimit {
+                address: "alice".to_string(),
+                limit: Some(Uint128::new(100)),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(600);
+            for _ in 0..2 {
+                do_transfer(deps.as_mut(), env.clone(), "alice", "shop", 50).unwrap();
+            }
+            let err = do_transfer(deps.as_mut(), env, "alice", "shop", 50).unwrap_err();
+            assert_eq!(err, ContractError::RateLimited { remaining: Uint128::zero() });
+        }
+
+        #[test]
+        fn burns_and_locks_count_as_outflow() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let msg = ExecuteMsg::SetRateLimit {
+                address: "alice".to_string(),
+                limit: Some(Uint128::new(100)),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let msg = ExecuteMsg::UpdateSavingsConfig {
+                tax_level: 0,
+                unbonding_period: cw_utils::Duration::Time(100),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            let msg = ExecuteMsg::Burn { amount: Uint128::new(60) };
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+            let msg = ExecuteMsg::Lock {
+                amount: Uint128::new(50),
+                duration: cw_utils::Duration::Time(60),
+            };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::RateLimited { remaining: Uint128::new(40) });
+            assert_eq!(query_limit(deps.as_ref(), mock_env(), "alice").used, Uint128::new(60));
+        }
+    }
+
+    mod membership {
+        use super::*;
+        use crate::query::MembershipGroupResponse;
+        use cosmwasm_std::{ContractResult, SystemError, SystemResult, WasmQuery};
+
+        #[test]
+        fn only_members_send_and_receive() {
+            let mut deps = mock_dependencies();
+            do_instantiate_with_minter(deps.as_mut(), "alice", Uint128::new(1000), "minter", None);
+            // the group answers raw member lookups, alice and bob are members
+            deps.querier.update_wasm(|query| match query {
+                WasmQuery::Raw { contract_addr, key } if contract_addr == "group" => {
+                    let member = key.ends_with(b"alice") || key.ends_with(b"bob");
+                    let weight = if member { to_binary(&1u64).unwrap() } else { Binary::default() };
+                    SystemResult::Ok(ContractResult::Ok(weight))
+                }
+                _ => SystemResult::Err(SystemError::Unknown {}),
+            });
+
+            let msg = ExecuteMsg::SetMembershipGroup { group: Some("group".to_string()) };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg.clone()).unwrap_err();
+            assert!(matches!(err, ContractError::Admin(_)));
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            let group: MembershipGroupResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::MembershipGroup {}).unwrap()).unwrap();
+            assert_eq!(group.group, Some("group".to_string()));
+
+            do_transfer(deps.as_mut(), mock_env(), "alice", "bob", 100).unwrap();
+            let err = do_transfer(deps.as_mut(), mock_env(), "alice", "carl", 10).unwrap_err();
+            assert_eq!(err, ContractError::NotMember { address: "carl".to_string() });
+
+            let msg = ExecuteMsg::Mint {
+                recipient: "carl".to_string(),
+                amount: Uint128::new(10),
+            };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::NotMember { address: "carl".to_string() });
+
+            // clearing the group opens the token again
+            let msg = ExecuteMsg::SetMembershipGroup { group: None };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            do_transfer(deps.as_mut(), mock_env(), "alice", "carl", 10).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "carl"), Uint128::new(10));
+        }
+    }
+
+    mod ubi {
+        use super::*;
+        use crate::query::UbiStatusResponse;
+        use cosmwasm_std::{ContractResult, SystemError, SystemResult, WasmQuery};
+        use cw4::{Cw4QueryMsg, Member, MemberListResponse};
+
+        fn status(deps: Deps) -> UbiStatusResponse {
+            from_binary(&query(deps, mock_env(), QueryMsg::UbiStatus {}).unwrap()).unwrap()
+        }
+
+        fn distribute(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+            let msg = ExecuteMsg::Distribute { limit: Some(1) };
+            execute(deps, env, mock_info("anyone", &[]), msg)
+        }
+
+        #[test]
+        fn distributes_weighted_shares_in_pages() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(20_000_000_000));
+            // alice holds three quarters of the group weight, bob the rest
+            deps.querier.update_wasm(|query| {
+                let members = vec![
+                    Member { addr: "alice".to_string(), weight: 3 },
+                    Member { addr: "bob".to_string(), weight: 1 },
+                ];
+                match query {
+                    WasmQuery::Raw { contract_addr, .. } if contract_addr == "group" => {
+                        SystemResult::Ok(ContractResult::Ok(to_binary(&4u64).unwrap()))
+                    }
+                    WasmQuery::Smart { contract_addr, msg } if contract_addr == "group" => {
+                        match from_binary(msg).unwrap() {
+                            Cw4QueryMsg::ListMembers { start_after, limit } => {
+                                let members = members
+                                    .into_iter()
+                                    .filter(|m| Some(&m.addr) > start_after.as_ref())
+                                    .take(limit.unwrap() as usize)
+                                    .collect();
+                                let res = MemberListResponse { members };
+                                SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                            }
+                            _ => SystemResult::Err(SystemError::Unknown {}),
+                        }
+                    }
+                    _ => SystemResult::Err(SystemError::Unknown {}),
+                }
+            });
+
+            let msg = ExecuteMsg::SetUbiGroup { group: Some("group".to_string()) };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg.clone()).unwrap_err();
+            assert!(matches!(err, ContractError::Admin(_)));
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            // nothing was collected yet
+            let err = distribute(deps.as_mut(), mock_env()).unwrap_err();
+            assert_eq!(err, ContractError::NothingToDistribute {});
+
+            // the next period collects the demurrage and starts a round paying alice
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(6000);
+            let alice_before = balances().load(&deps.storage, &Addr::unchecked("alice")).unwrap();
+            distribute(deps.as_mut(), env.clone()).unwrap();
+            let round = status(deps.as_ref()).round.unwrap();
+            assert!(!round.pool.is_zero());
+            assert_eq!(round.cursor, Some("alice".to_string()));
+            let alice_after = balances().load(&deps.storage, &Addr::unchecked("alice")).unwrap();
+            assert_eq!(alice_after - alice_before, round.pool.multiply_ratio(3u128, 4u128));
+
+            distribute(deps.as_mut(), env.clone()).unwrap();
+            let bob = balances().load(&deps.storage, &Addr::unchecked("bob")).unwrap();
+            assert_eq!(bob, round.pool.multiply_ratio(1u128, 4u128));
+
+            // the last page ends the round
+            let res = distribute(deps.as_mut(), env).unwrap();
+            assert!(res.attributes.contains(&attr("round", "finished")));
+            // the missed periods were all collected by the round
+            let ubi = status(deps.as_ref());
+            assert_eq!(ubi.round, None);
+            assert!(ubi.pending.is_zero());
+        }
+    }
+
+    mod ownership {
+        use super::*;
+        use crate::query::PendingOwnershipResponse;
+        use cw_controllers::AdminResponse;
+        use cw_utils::Expiration;
+
+        fn pending(deps: Deps) -> PendingOwnershipResponse {
+            from_binary(&query(deps, mock_env(), QueryMsg::PendingOwnership {}).unwrap()).unwrap()
+        }
+
+        #[test]
+        fn minter_handover_needs_acceptance() {
+            let mut deps = mock_dependencies();
+            do_instantiate_with_minter(deps.as_mut(), "alice", Uint128::new(1000), "minter", None);
+
+            let msg = ExecuteMsg::ProposeMinter {
+                new_minter: "new_minter".to_string(),
+                expires: None,
+            };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg.clone()).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+            execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+
+            // the minter does not change until the proposal is accepted
+            assert_eq!(query_minter(deps.as_ref()).unwrap().unwrap().minter, "minter");
+            let proposal = pending(deps.as_ref()).minter.unwrap();
+            assert_eq!(proposal.new_owner, Addr::unchecked("new_minter"));
+
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), ExecuteMsg::AcceptMinter {}).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+            execute(deps.as_mut(), mock_env(), mock_info("new_minter", &[]), ExecuteMsg::AcceptMinter {}).unwrap();
+            assert_eq!(query_minter(deps.as_ref()).unwrap().unwrap().minter, "new_minter");
+            assert_eq!(pending(deps.as_ref()), PendingOwnershipResponse::default());
+        }
+
+        #[test]
+        fn admin_proposal_expires_and_cancels() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+
+            let env = mock_env();
+            let msg = ExecuteMsg::ProposeAdmin {
+                new_admin: "bob".to_string(),
+                expires: Some(Expiration::AtHeight(env.block.height + 10)),
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg.clone()).unwrap();
+
+            let mut late = env.clone();
+            late.block.height += 10;
+            let err = execute(deps.as_mut(), late, mock_info("bob", &[]), ExecuteMsg::AcceptAdmin {}).unwrap_err();
+            assert_eq!(err, ContractError::OwnershipProposalExpired {});
+
+            // cancelled proposals can not be accepted either
+            execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), ExecuteMsg::CancelAdminProposal {}).unwrap();
+            let err = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), ExecuteMsg::AcceptAdmin {}).unwrap_err();
+            assert_eq!(err, ContractError::NoPendingOwnership {});
+
+            execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+            execute(deps.as_mut(), env, mock_info("bob", &[]), ExecuteMsg::AcceptAdmin {}).unwrap();
+            let admin: AdminResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Admin {}).unwrap()).unwrap();
+            assert_eq!(admin.admin, Some("bob".to_string()));
+            assert!(pending(deps.as_ref()).admin.is_none());
+        }
+
+        #[test]
+        fn update_admin_only_proposes() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let query_admin = |deps: Deps| -> AdminResponse {
+                from_binary(&query(deps, mock_env(), QueryMsg::Admin {}).unwrap()).unwrap()
+            };
+
+            let msg = ExecuteMsg::UpdateAdmin { admin: Some("bob".to_string()) };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            assert_eq!(query_admin(deps.as_ref()).admin, Some("creator".to_string()));
+            assert_eq!(pending(deps.as_ref()).admin.unwrap().new_owner, Addr::unchecked("bob"));
+
+            // giving up the role drops the proposal with it
+            let msg = ExecuteMsg::UpdateAdmin { admin: None };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            assert_eq!(query_admin(deps.as_ref()).admin, None);
+            assert!(pending(deps.as_ref()).admin.is_none());
+            let err = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), ExecuteMsg::AcceptAdmin {}).unwrap_err();
+            assert_eq!(err, ContractError::NoPendingOwnership {});
+        }
+    }
+
+    mod simulate {
+        use super::*;
+        use crate::query::{AccountBalance, SimulateResponse};
+        use crate::state::UBI_GROUP;
+        use cw4::Cw4Contract;
+
+        fn simulate(deps: Deps, env: Env, sender: &str, msg: ExecuteMsg) -> SimulateResponse {
+            let msg = QueryMsg::Simulate { sender: sender.to_string(), msg };
+            from_binary(&query(deps, env, msg).unwrap()).unwrap()
+        }
+
+        #[test]
+        fn previews_transfer_without_storing() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+
+            let msg = ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(300),
+            };
+            let res = simulate(deps.as_ref(), mock_env(), "alice", msg);
+            assert_eq!(res.error, None);
+            assert_eq!(
+                res.balances,
+                vec![
+                    AccountBalance { address: "alice".to_string(), balance: Uint128::new(700) },
+                    AccountBalance { address: "bob".to_string(), balance: Uint128::new(300) },
+                ]
+            );
+            assert_eq!(res.redistributed, Uint128::zero());
+            // nothing was stored
+            assert_eq!(get_balance(deps.as_ref(), "alice"), Uint128::new(1000));
+            assert_eq!(get_balance(deps.as_ref(), "bob"), Uint128::zero());
+
+            let msg = ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(3000),
+            };
+            let res = simulate(deps.as_ref(), mock_env(), "alice", msg);
+            assert!(res.error.is_some());
+            assert!(res.balances.is_empty());
+        }
+
+        #[test]
+        fn previews_demurrage_and_redistribution() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(20_000_000_000));
+            let before: DemurrageAmountResponse = from_binary(
+                &query(deps.as_ref(), mock_env(), QueryMsg::DemurrageAmount {}).unwrap(),
+            )
+            .unwrap();
+
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(6000);
+            let msg = ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(1000),
+            };
+            let res = simulate(deps.as_ref(), env.clone(), "alice", msg.clone());
+            assert_eq!(res.error, None);
+            assert!(res.demurrage_amount < before.demurrage_amount);
+            // all periods passed in the meantime are closed at once
+            assert_eq!(res.current_period, 101);
+            assert!(!res.redistributed.is_zero());
+            // the sink receives the collected demurrage
+            assert_eq!(res.balances.len(), 3);
+
+            // the preview matches what executing the message redistributes
+            let sink = Addr::unchecked("sinkaddress");
+            let sink_before = balances().may_load(&deps.storage, &sink).unwrap().unwrap_or_default();
+            execute(deps.as_mut(), env, mock_info("alice", &[]), msg).unwrap();
+            let sink_after = balances().load(&deps.storage, &sink).unwrap();
+            assert_eq!(sink_after - sink_before, res.redistributed);
+        }
+
+        #[test]
+        fn redistribution_without_reserved_parts() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(20_000_000_000));
+            // with a basic income group the demurrage waits for the next round
+            UBI_GROUP.save(deps.as_mut().storage, &Cw4Contract::new(Addr::unchecked("group"))).unwrap();
+
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(6000);
+            let msg = ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(1000),
+            };
+            let res = simulate(deps.as_ref(), env, "alice", msg);
+            assert_eq!(res.error, None);
+            assert_eq!(res.current_period, 101);
+            assert_eq!(res.redistributed, Uint128::zero());
+        }
+    }
+
+    mod schedule {
+        use super::*;
+        use crate::query::ScheduledTransfersResponse;
+        use cw_utils::Scheduled;
+
+        fn scheduled(deps: Deps, sender: Option<&str>) -> ScheduledTransfersResponse {
+            let msg = QueryMsg::Scheduled {
+                sender: sender.map(String::from),
+                start_after: None,
+                limit: None,
+            };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        }
+
+        fn schedule(deps: DepsMut, from: &str, to: &str, amount: u128, at: Scheduled) -> Result<Response, ContractError> {
+            let msg = ExecuteMsg::ScheduleTransfer {
+                recipient: to.to_string(),
+                amount: Uint128::new(amount),
+                at,
+            };
+            execute(deps, mock_env(), mock_info(from, &[]), msg)
+        }
+
+        #[test]
+        fn releases_escrow_once_triggered() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let height = mock_env().block.height;
+
+            let err = schedule(deps.as_mut(), "alice", "bob", 100, Scheduled::AtHeight(height)).unwrap_err();
+            assert_eq!(err, ContractError::ScheduleInPast {});
+
+            schedule(deps.as_mut(), "alice", "bob", 100, Scheduled::AtHeight(height + 10)).unwrap();
+            schedule(deps.as_mut(), "alice", "carl", 200, Scheduled::AtHeight(height + 20)).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "alice"), Uint128::new(700));
+            let pending = scheduled(deps.as_ref(), Some("alice"));
+            assert_eq!(pending.transfers.len(), 2);
+            assert_eq!(pending.transfers[0].recipient, "bob");
+            assert_eq!(scheduled(deps.as_ref(), Some("bob")).transfers, vec![]);
+
+            // nothing is due yet
+            let msg = ExecuteMsg::ExecuteScheduled { limit: None };
+            execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), msg.clone()).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "bob"), Uint128::zero());
+
+            let mut env = mock_env();
+            env.block.height += 10;
+            let res = execute(deps.as_mut(), env, mock_info("keeper", &[]), msg).unwrap();
+            assert_eq!(res.events.len(), 1);
+            assert_eq!(get_balance(deps.as_ref(), "bob"), Uint128::new(100));
+
+            // only the sender may cancel, the escrow goes back to them
+            let id = scheduled(deps.as_ref(), None).transfers[0].id;
+            let msg = ExecuteMsg::CancelScheduled { id };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("carl", &[]), msg.clone()).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg.clone()).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "alice"), Uint128::new(900));
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::ScheduledNotFound { id });
+        }
+
+        #[test]
+        fn releases_by_due_time_within_the_limit() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let env = mock_env();
+
+            // created out of release order, the far one first
+            schedule(deps.as_mut(), "alice", "dave", 400, Scheduled::AtHeight(env.block.height + 1000)).unwrap();
+            schedule(deps.as_mut(), "alice", "carl", 200, Scheduled::AtTime(env.block.time.plus_seconds(60))).unwrap();
+            schedule(deps.as_mut(), "alice", "bob", 100, Scheduled::AtHeight(env.block.height + 10)).unwrap();
+
+            let mut later = mock_env();
+            later.block.height += 10;
+            later.block.time = later.block.time.plus_seconds(60);
+            let msg = ExecuteMsg::ExecuteScheduled { limit: Some(1) };
+            let res = execute(deps.as_mut(), later.clone(), mock_info("keeper", &[]), msg.clone()).unwrap();
+            assert_eq!(res.events.len(), 1);
+            assert_eq!(get_balance(deps.as_ref(), "bob"), Uint128::new(100));
+            assert_eq!(get_balance(deps.as_ref(), "carl"), Uint128::zero());
+
+            execute(deps.as_mut(), later.clone(), mock_info("keeper", &[]), msg.clone()).unwrap();
+            assert_eq!(get_balance(deps.as_ref(), "carl"), Uint128::new(200));
+
+            // the far one is never visited
+            let res = execute(deps.as_mut(), later, mock_info("keeper", &[]), msg).unwrap();
+            assert_eq!(res.events.len(), 0);
+            assert_eq!(get_balance(deps.as_ref(), "dave"), Uint128::zero());
+            assert_eq!(scheduled(deps.as_ref(), None).transfers.len(), 1);
+        }
+
+        #[test]
+        fn frozen_recipients_do_not_block_the_queue() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let height = mock_env().block.height;
+
+            for _ in 0..3 {
+                schedule(deps.as_mut(), "alice", "bob", 10, Scheduled::AtHeight(height + 1)).unwrap();
+            }
+            schedule(deps.as_mut(), "alice", "carl", 100, Scheduled::AtHeight(height + 2)).unwrap();
+            let msg = ExecuteMsg::Freeze { address: "bob".to_string() };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            let mut later = mock_env();
+            later.block.height += 2;
+            let msg = ExecuteMsg::ExecuteScheduled { limit: Some(2) };
+            let res = execute(deps.as_mut(), later, mock_info("keeper", &[]), msg).unwrap();
+            assert_eq!(res.events.len(), 1);
+            assert_eq!(get_balance(deps.as_ref(), "carl"), Uint128::new(100));
+            // bob's transfers stay in escrow
+            assert_eq!(get_balance(deps.as_ref(), "bob"), Uint128::zero());
+            assert_eq!(scheduled(deps.as_ref(), None).transfers.len(), 3);
+        }
+    }
+
+    mod subscriptions {
+        use super::*;
+        use crate::query::SubscriptionsResponse;
+        use cw_utils::Duration;
+
+        fn by_payer(deps: Deps, payer: &str) -> SubscriptionsResponse {
+            let msg = QueryMsg::SubscriptionsByPayer {
+                payer: payer.to_string(),
+                start_after: None,
+                limit: None,
+            };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        }
+
+        fn process(deps: DepsMut, env: Env) -> Response {
+            let msg = ExecuteMsg::ProcessSubscriptions { limit: None };
+            execute(deps, env, mock_info("keeper", &[]), msg).unwrap()
+        }
+
+        #[test]
+        fn charges_from_allowance_each_interval() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let msg = ExecuteMsg::IncreaseAllowance {
+                spender: "bob".to_string(),
+                amount: Uint128::new(100),
+                expires: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+
+            let subscribe = |payee: &str| ExecuteMsg::CreateSubscription {
+                payee: payee.to_string(),
+                amount: Uint128::new(60),
+                interval: Duration::Height(10),
+                max_payments: Some(2),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), subscribe("bob")).unwrap();
+            // carl has no allowance to charge from
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), subscribe("carl")).unwrap();
+            assert_eq!(by_payer(deps.as_ref(), "alice").subscriptions.len(), 2);
+
+            let res = process(deps.as_mut(), mock_env());
+            assert!(res.attributes.contains(&attr("charged", "1")));
+            assert_eq!(get_balance(deps.as_ref(), "bob"), Uint128::new(60));
+            assert_eq!(get_balance(deps.as_ref(), "carl"), Uint128::zero());
+            assert_eq!(get_balance(deps.as_ref(), "alice"), Uint128::new(940));
+            // the failed payment cancelled carl's subscription
+            assert_eq!(by_payer(deps.as_ref(), "alice").subscriptions.len(), 1);
+
+            // nothing is due until the next interval
+            let res = process(deps.as_mut(), mock_env());
+            assert!(res.attributes.contains(&attr("charged", "0")));
+
+            let mut env = mock_env();
+            env.block.height += 10;
+            process(deps.as_mut(), env);
+            assert_eq!(get_balance(deps.as_ref(), "bob"), Uint128::new(120));
+
+            // the last payment ends the subscription, the payee may cancel a new one
+            let msg = QueryMsg::SubscriptionsByPayee {
+                payee: "bob".to_string(),
+                start_after: None,
+                limit: None,
+            };
+            let by_payee: SubscriptionsResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(by_payee.subscriptions, vec![]);
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), subscribe("carl")).unwrap();
+            let id = by_payer(deps.as_ref(), "alice").subscriptions[0].id;
+            let msg = ExecuteMsg::CancelSubscription { id };
+            let err = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), msg.clone()).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+            execute(deps.as_mut(), mock_env(), mock_info("carl", &[]), msg).unwrap();
+            assert_eq!(by_payer(deps.as_ref(), "alice").subscriptions, vec![]);
+        }
+
+        #[test]
+        fn failed_payment_cancels_and_does_not_block_others() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let msg = ExecuteMsg::IncreaseAllowance {
+                spender: "carl".to_string(),
+                amount: Uint128::new(100),
+                expires: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+            let subscribe = |payee: &str, interval: Duration| ExecuteMsg::CreateSubscription {
+                payee: payee.to_string(),
+                amount: Uint128::new(50),
+                interval,
+                max_payments: None,
+            };
+            // bob has no allowance, his subscription is due first
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), subscribe("bob", Duration::Height(10))).unwrap();
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), subscribe("carl", Duration::Time(60))).unwrap();
+
+            let msg = ExecuteMsg::ProcessSubscriptions { limit: Some(1) };
+            let res = execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), msg.clone()).unwrap();
+            assert!(res.attributes.contains(&attr("charged", "0")));
+            let subscriptions = by_payer(deps.as_ref(), "alice").subscriptions;
+            assert_eq!(subscriptions.len(), 1);
+            assert_eq!(subscriptions[0].subscription.payee, "carl");
+
+            let res = execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), msg.clone()).unwrap();
+            assert!(res.attributes.contains(&attr("charged", "1")));
+            assert_eq!(get_balance(deps.as_ref(), "carl"), Uint128::new(50));
+
+            // not due again before the interval passed
+            let res = execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), msg).unwrap();
+            assert!(res.attributes.contains(&attr("charged", "0")));
+            assert_eq!(by_payer(deps.as_ref(), "alice").subscriptions.len(), 1);
+        }
+
+        #[test]
+        fn transient_failure_is_retried_next_interval() {
+            let mut deps = mock_dependencies();
+            do_instantiate(deps.as_mut(), "alice", Uint128::new(1000));
+            let msg = ExecuteMsg::IncreaseAllowance {
+                spender: "bob".to_string(),
+                amount: Uint128::new(100),
+                expires: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+            let msg = ExecuteMsg::CreateSubscription {
+                payee: "bob".to_string(),
+                amount: Uint128::new(50),
+                interval: Duration::Height(10),
+                max_payments: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+
+            let msg = ExecuteMsg::Freeze { address: "bob".to_string() };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let res = process(deps.as_mut(), mock_env());
+            assert!(res.attributes.contains(&attr("charged", "0")));
+            let subscriptions = by_payer(deps.as_ref(), "alice").subscriptions;
+            assert_eq!(subscriptions.len(), 1);
+            let mut env = mock_env();
+            env.block.height += 10;
+            assert_eq!(subscriptions[0].subscription.next_payment, Expiration::AtHeight(env.block.height));
+
+            let msg = ExecuteMsg::Unfreeze { address: "bob".to_string() };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let res = process(deps.as_mut(), env);
+            assert!(res.attributes.contains(&attr("charged", "1")));
+            assert_eq!(get_balance(deps.as_ref(), "bob"), Uint128::new(50));
+        }
+    }
+}
@@ -1,13 +1,23 @@
-use cosmwasm_std::{Deps, Order, StdResult};
+use std::cmp::Reverse;
+
+use cosmwasm_std::{Addr, Deps, Env, Order, StdResult, Uint128};
 use cw20::{AllAccountsResponse, AllAllowancesResponse, AllowanceInfo};
 
-use crate::state::{ALLOWANCES, BALANCES};
+use crate::query::{AccountBalance, BalancesResponse, FrozenAccountsResponse, MinterInfo, MintersResponse};
+use crate::state::{ALLOWANCES, FROZEN, MINTERS, STATE, balances};
+use crate::tiers::account_worth;
 use cw_storage_plus::Bound;
 
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
 
+/// Number of entries a page, or a batch of work, takes: the default when none is
+/// given and never more than the maximum
+pub fn page_limit(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize
+}
+
 pub fn query_all_allowances(
     deps: Deps,
     owner: String,
@@ -15,7 +25,7 @@ pub fn query_all_allowances(
     limit: Option<u32>,
 ) -> StdResult<AllAllowancesResponse> {
     let owner_addr = deps.api.addr_validate(&owner)?;
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let limit = page_limit(limit);
     let start = start_after.map(|s| Bound::ExclusiveRaw(s.into_bytes()));
 
     let allowances = ALLOWANCES
@@ -38,10 +48,10 @@ pub fn query_all_accounts(
     start_after: Option<String>,
     limit: Option<u32>,
 ) -> StdResult<AllAccountsResponse> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let limit = page_limit(limit);
     let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
 
-    let accounts = BALANCES
+    let accounts = balances()
         .keys(deps.storage, start, None, Order::Ascending)
         .take(limit)
         .map(|item| item.map(Into::into))
@@ -50,6 +60,123 @@ pub fn query_all_accounts(
     Ok(AllAccountsResponse { accounts })
 }
 
+/// Balances with the demurrage accrued up to the current block applied, by the class
+/// or tiers of each account
+fn to_account_balances(
+    deps: Deps,
+    env: &Env,
+    items: Vec<(Addr, Uint128)>,
+) -> StdResult<Vec<AccountBalance>> {
+    let state = STATE.load(deps.storage)?;
+    items
+        .into_iter()
+        .map(|(address, balance)| {
+            let balance = account_worth(deps.storage, &state, &address, balance, env.block.time)?;
+            Ok(AccountBalance {
+                address: address.into(),
+                balance,
+            })
+        })
+        .collect()
+}
+
+pub fn query_all_balances(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<BalancesResponse> {
+    let limit = page_limit(limit);
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let items = balances()
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    let balances = to_account_balances(deps, &env, items)?;
+    Ok(BalancesResponse { balances })
+}
+
+/// Accounts with the largest balances by worth, largest first. The worth of a balance
+/// never exceeds its stored amount, so the scan stops at the first stored amount that
+/// cannot beat the smallest worth listed.
+// is_none_or would need a newer compiler than the contract is built with
+#[allow(clippy::unnecessary_map_or)]
+pub fn query_top_holders(deps: Deps, env: Env, limit: Option<u32>) -> StdResult<BalancesResponse> {
+    let limit = page_limit(limit);
+    let state = STATE.load(deps.storage)?;
+
+    let mut balances_by_worth: Vec<AccountBalance> = vec![];
+    for item in balances()
+        .idx
+        .amount
+        .range(deps.storage, None, None, Order::Descending)
+    {
+        let (address, balance) = item?;
+        if balances_by_worth.len() == limit
+            && balances_by_worth.last().map_or(true, |last| last.balance >= balance)
+        {
+            break;
+        }
+        let balance = account_worth(deps.storage, &state, &address, balance, env.block.time)?;
+        balances_by_worth.push(AccountBalance {
+            address: address.into(),
+            balance,
+        });
+        balances_by_worth.sort_by_key(|entry| Reverse(entry.balance));
+        balances_by_worth.truncate(limit);
+    }
+    Ok(BalancesResponse { balances: balances_by_worth })
+}
+
+pub fn query_minters(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<MintersResponse> {
+    let limit = page_limit(limit);
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+    let period = STATE.load(deps.storage)?.current_period;
+
+    let minters = MINTERS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(addr, config)| MinterInfo {
+                minter: addr.into(),
+                cap: config.cap,
+                minted: config.minted,
+                per_period_limit: config.per_period_limit,
+                period_minted: if config.period == period {
+                    config.period_minted
+                } else {
+                    Uint128::zero()
+                },
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(MintersResponse { minters })
+}
+
+pub fn query_frozen_accounts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<FrozenAccountsResponse> {
+    let limit = page_limit(limit);
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let accounts = FROZEN
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(Into::into))
+        .collect::<StdResult<_>>()?;
+
+    Ok(FrozenAccountsResponse { accounts })
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
@@ -1,4 +1,6 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
+use cw_controllers::{AdminError, HookError};
+use cw_utils::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,15 +8,54 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Admin(#[from] AdminError),
+
+    #[error("{0}")]
+    Hook(#[from] HookError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Transfers are paused")]
+    Paused {},
+
+    #[error("Account {address} is frozen")]
+    AccountFrozen { address: String },
+
+    #[error("Account {address} is not a member of the membership group")]
+    NotMember { address: String },
+
     #[error("Cannot set to own account")]
     CannotSetOwnAccount {},
 
     #[error("Invalid zero amount")]
     InvalidZeroAmount {},
 
+    #[error("Cannot spend locked tokens, {locked} are still vesting")]
+    LockedTokens { locked: Uint128 },
+
+    #[error("Invalid vesting schedule")]
+    InvalidVesting {},
+
+    #[error("Permit is expired")]
+    PermitExpired {},
+
+    #[error("Invalid permit nonce, expected {expected}")]
+    InvalidNonce { expected: u64 },
+
+    #[error("Invalid permit signature")]
+    InvalidSignature {},
+
+    #[error("Public key does not belong to the permit owner")]
+    PubkeyMismatch {},
+
+    #[error("Batch cannot be empty")]
+    EmptyBatch {},
+
     #[error("Allowance is expired")]
     Expired {},
 
@@ -24,6 +65,12 @@ pub enum ContractError {
     #[error("Minting cannot exceed the cap")]
     CannotExceedCap {},
 
+    #[error("Minting cannot exceed the limit of the current period")]
+    CannotExceedPeriodLimit {},
+
+    #[error("Minter is not registered")]
+    MinterNotFound {},
+
     #[error("Logo binary data exceeds 5KB limit")]
     LogoTooBig {},
 
@@ -33,6 +80,66 @@ pub enum ContractError {
     #[error("Invalid png header")]
     InvalidPngHeader {},
 
+    #[error("Token is not backed by a native reserve")]
+    NoReserve {},
+
+    #[error("Not enough native coins in the reserve")]
+    InsufficientReserve {},
+
+    #[error("Tax tiers must be contiguous, with increasing bounds and rates, and end unbounded")]
+    InvalidTaxTiers {},
+
+    #[error("Tax level cannot take more than the whole balance")]
+    InvalidTaxLevel {},
+
+    #[error("Account class does not exist")]
+    ClassNotFound {},
+
+    #[error("Rebate share cannot exceed 1")]
+    InvalidRebateShare {},
+
+    #[error("No rebate to claim for this period")]
+    NoRebate {},
+
+    #[error("No ownership transfer is pending")]
+    NoPendingOwnership {},
+
+    #[error("The ownership proposal is expired")]
+    OwnershipProposalExpired {},
+
+    #[error("The schedule has already triggered")]
+    ScheduleInPast {},
+
+    #[error("No scheduled transfer with id {id}")]
+    ScheduledNotFound { id: u64 },
+
+    #[error("Subscription interval must not be zero")]
+    InvalidSubscriptionInterval {},
+
+    #[error("No subscription with id {id}")]
+    SubscriptionNotFound { id: u64 },
+
+    #[error("No UBI group is configured")]
+    UbiDisabled {},
+
+    #[error("Nothing to distribute")]
+    NothingToDistribute {},
+
+    #[error("Transfer exceeds the rate limit, {remaining} left in this period")]
+    RateLimited { remaining: Uint128 },
+
+    #[error("Savings locks are not enabled")]
+    SavingsDisabled {},
+
+    #[error("Savings tax level cannot exceed the demurrage tax level")]
+    InvalidSavingsTax {},
+
+    #[error("No lock has reached the end of its term")]
+    NothingToUnlock {},
+
+    #[error("No unlocked tokens are ready to be claimed")]
+    NothingToClaim {},
+
     #[error("Duplicate initial balance addresses")]
     DuplicateInitialBalanceAddresses {},
 }
@@ -0,0 +1,92 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Empty, MessageInfo, Response, StdResult, Storage};
+use cw_utils::maybe_addr;
+
+use crate::error::ContractError;
+use crate::query::PausedResponse;
+use crate::state::{ADMIN, FROZEN, GUARDIAN, PAUSED};
+
+/// Fails if the contract is paused or any of the accounts is frozen.
+/// Every handler that changes balances calls this with all accounts involved.
+pub fn assert_can_transfer(storage: &dyn Storage, accounts: &[&Addr]) -> Result<(), ContractError> {
+    if PAUSED.may_load(storage)?.unwrap_or_default() {
+        return Err(ContractError::Paused {});
+    }
+    for addr in accounts {
+        if FROZEN.has(storage, addr) {
+            return Err(ContractError::AccountFrozen {
+                address: addr.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn assert_admin_or_guardian(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    if ADMIN.is_admin(deps, sender)? || GUARDIAN.is_admin(deps, sender)? {
+        Ok(())
+    } else {
+        Err(ContractError::Unauthorized {})
+    }
+}
+
+pub fn execute_freeze(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    frozen: bool,
+) -> Result<Response, ContractError> {
+    assert_admin_or_guardian(deps.as_ref(), &info.sender)?;
+
+    let addr = deps.api.addr_validate(&address)?;
+    if frozen {
+        FROZEN.save(deps.storage, &addr, &Empty {})?;
+    } else {
+        FROZEN.remove(deps.storage, &addr);
+    }
+
+    let action = if frozen { "freeze" } else { "unfreeze" };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("address", address)
+        .add_attribute("by", info.sender))
+}
+
+pub fn execute_pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    assert_admin_or_guardian(deps.as_ref(), &info.sender)?;
+
+    PAUSED.save(deps.storage, &paused)?;
+
+    let action = if paused { "pause" } else { "unpause" };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("by", info.sender))
+}
+
+pub fn execute_update_guardian(
+    deps: DepsMut,
+    info: MessageInfo,
+    guardian: Option<String>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let guardian = maybe_addr(deps.api, guardian)?;
+    let guardian_str = guardian
+        .as_ref()
+        .map(|g| g.to_string())
+        .unwrap_or_else(|| "None".to_string());
+    GUARDIAN.set(deps, guardian)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_guardian")
+        .add_attribute("guardian", guardian_str)
+        .add_attribute("sender", info.sender))
+}
+
+pub fn query_paused(deps: Deps) -> StdResult<PausedResponse> {
+    let paused = PAUSED.may_load(deps.storage)?.unwrap_or_default();
+    Ok(PausedResponse { paused })
+}
@@ -0,0 +1,74 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, Addr, Binary, CosmosMsg, StdResult, Uint128, WasmMsg};
+
+/// BalanceDiff shows the old and new balance of a given account
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BalanceDiff {
+    pub address: String,
+    pub old: Uint128,
+    pub new: Uint128,
+}
+
+impl BalanceDiff {
+    pub fn new<T: Into<String>>(addr: T, old_balance: Uint128, new_balance: Uint128) -> Self {
+        BalanceDiff {
+            address: addr.into(),
+            old: old_balance,
+            new: new_balance,
+        }
+    }
+
+    /// diff for an account that received `amount`, given its balance after the update
+    pub fn credited(addr: &Addr, new_balance: Uint128, amount: Uint128) -> Self {
+        BalanceDiff::new(addr, new_balance - amount, new_balance)
+    }
+
+    /// diff for an account that lost `amount`, given its balance after the update
+    pub fn debited(addr: &Addr, new_balance: Uint128, amount: Uint128) -> Self {
+        BalanceDiff::new(addr, new_balance + amount, new_balance)
+    }
+}
+
+/// BalanceChangedHookMsg should be de/serialized under `BalanceChangedHook()` variant in a ExecuteMsg.
+/// This contains a list of all balance diffs on the given transaction.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct BalanceChangedHookMsg {
+    pub diffs: Vec<BalanceDiff>,
+}
+
+impl BalanceChangedHookMsg {
+    pub fn one(diff: BalanceDiff) -> Self {
+        BalanceChangedHookMsg { diffs: vec![diff] }
+    }
+
+    pub fn new(diffs: Vec<BalanceDiff>) -> Self {
+        BalanceChangedHookMsg { diffs }
+    }
+
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = BalanceChangedExecuteMsg::BalanceChangedHook(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+// This is just a helper to properly serialize the above message
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+enum BalanceChangedExecuteMsg {
+    BalanceChangedHook(BalanceChangedHookMsg),
+}
@@ -1,10 +1,30 @@
 pub mod allowances;
+pub mod classes;
 pub mod contract;
 pub mod enumerable;
 mod error;
+pub mod freeze;
+pub mod hook;
+pub mod membership;
 pub mod msg;
+pub mod ownership;
+pub mod permit;
+pub mod projection;
 pub mod state;
+pub mod vesting;
 pub mod query;
+pub mod ratelimit;
+pub mod rebates;
+pub mod reserve;
+pub mod savings;
+pub mod schedule;
+pub mod simulate;
+pub mod stats;
+pub mod subscriptions;
+pub mod sweep;
+pub mod tiers;
+pub mod twab;
+pub mod ubi;
 
 
 pub use crate::error::ContractError;
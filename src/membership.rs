@@ -0,0 +1,55 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, MessageInfo, Response, StdResult};
+use cw4::Cw4Contract;
+
+use crate::error::ContractError;
+use crate::query::MembershipGroupResponse;
+use crate::state::{ADMIN, MEMBERSHIP_GROUP};
+
+/// Fails if a membership group is set and any of the accounts is not part of it.
+/// Called next to assert_can_transfer for every account that sends or receives tokens.
+pub fn assert_members(deps: Deps, accounts: &[&Addr]) -> Result<(), ContractError> {
+    let group = match MEMBERSHIP_GROUP.may_load(deps.storage)? {
+        Some(group) => group,
+        None => return Ok(()),
+    };
+    for addr in accounts {
+        if group.is_member(&deps.querier, addr, None)?.is_none() {
+            return Err(ContractError::NotMember {
+                address: addr.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+pub fn execute_set_membership_group(
+    deps: DepsMut,
+    info: MessageInfo,
+    group: Option<String>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let group_str = match group {
+        Some(group) => {
+            let addr = deps.api.addr_validate(&group)?;
+            MEMBERSHIP_GROUP.save(deps.storage, &Cw4Contract::new(addr))?;
+            group
+        }
+        None => {
+            MEMBERSHIP_GROUP.remove(deps.storage);
+            "None".to_string()
+        }
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "set_membership_group")
+        .add_attribute("group", group_str)
+        .add_attribute("sender", info.sender))
+}
+
+pub fn query_membership_group(deps: Deps) -> StdResult<MembershipGroupResponse> {
+    let group = MEMBERSHIP_GROUP
+        .may_load(deps.storage)?
+        .map(|group| group.addr().to_string());
+    Ok(MembershipGroupResponse { group })
+}
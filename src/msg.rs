@@ -1,8 +1,10 @@
-use cosmwasm_std::{StdError, StdResult, Uint128, Binary};
+use cosmwasm_std::{Decimal, StdError, StdResult, Timestamp, Uint128, Binary};
 use cw20::{Cw20Coin, Logo, MinterResponse};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use cw_utils::Expiration;
+
+use crate::state::TaxTier;
+use cw_utils::{Duration, Expiration, Scheduled};
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct InstantiateMarketingInfo {
@@ -12,13 +14,44 @@ pub struct InstantiateMarketingInfo {
     pub logo: Option<Logo>,
 }
 
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct InstantiateReserveInfo {
+    pub denom: String,
+    /// tokens minted for one unit of the native coin
+    pub ratio: Decimal,
+    /// share of the redeemed tokens sent to the sink
+    pub redemption_fee: Decimal,
+}
+
+/// Nothing is released before the cliff, then tokens unlock linearly until end.
+/// Both points must be heights or both must be times.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct VestingSchedule {
+    pub cliff: Scheduled,
+    pub end: Scheduled,
+}
+
+/// Locks the whole initial balance of address under the schedule
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct InitialVesting {
+    pub address: String,
+    pub schedule: VestingSchedule,
+}
+
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct InstantiateMsg {
     pub name: String,
     pub symbol: String,
     pub decimals: u32,
     pub initial_balances: Vec<Cw20Coin>,
+    /// vesting schedules for some of the initial balances
+    #[serde(default)]
+    pub initial_vesting: Vec<InitialVesting>,
     pub mint: Option<MinterResponse>,
+    /// admin allowed to manage the balance hooks
+    pub admin: Option<String>,
+    /// native coin backing the token, enables Deposit and Redeem
+    pub reserve: Option<InstantiateReserveInfo>,
     
     // demurrage variable 
     pub tax_level_minute: u128,
@@ -49,6 +82,14 @@ impl InstantiateMsg {
         if self.decimals > 18 {
             return Err(StdError::generic_err("Decimals must not exceed 18"));
         }
+        if let Some(reserve) = &self.reserve {
+            if reserve.ratio.is_zero() {
+                return Err(StdError::generic_err("Reserve ratio must be greater than zero"));
+            }
+            if reserve.redemption_fee >= Decimal::one() {
+                return Err(StdError::generic_err("Redemption fee must be lower than 100%"));
+            }
+        }
         Ok(())
     }
 }
@@ -76,11 +117,31 @@ fn is_valid_symbol(symbol: &str) -> bool {
 
 
 
+/// Transfer signed by the owner, which anyone can submit on their behalf
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Permit {
+    pub owner: String,
+    pub recipient: String,
+    pub amount: Uint128,
+    /// must match the current nonce of the owner, see QueryMsg::Nonce
+    pub nonce: u64,
+    pub expires: Expiration,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     /// Transfer is a base message to move tokens to another account without triggering actions
     Transfer { recipient: String, amount: Uint128 },
+    /// Transfer to several accounts at once. Fails as a whole if any transfer fails
+    TransferBatch { transfers: Vec<Cw20Coin> },
+    /// Transfer on behalf of permit.owner. The signature is a secp256k1 signature of
+    /// the sha256 hash of the PermitSignDoc, made with the key the owner address derives from
+    TransferWithPermit {
+        permit: Permit,
+        signature: Binary,
+        pubkey: Binary,
+    },
     /// Burn is a base message to destroy tokens forever
     Burn { amount: Uint128 },
     /// Send is a base message to transfer tokens to a contract and trigger an action
@@ -126,13 +187,167 @@ pub enum ExecuteMsg {
     /// Only with the "mintable" extension. If authorized, creates amount new tokens
     /// and adds to the recipient balance.
     Mint { recipient: String, amount: Uint128 },
-    /// Only with the "mintable" extension. The current minter may set a new minter.
+    /// Only with the "mintable" extension. Mint to several accounts at once, the caps
+    /// apply to the sum of all amounts. Fails as a whole if any mint fails
+    MintBatch { mints: Vec<Cw20Coin> },
+    /// Only with the "mintable" extension. Like Mint, but the minted tokens are locked
+    /// and released according to the schedule.
+    MintVesting {
+        recipient: String,
+        amount: Uint128,
+        schedule: VestingSchedule,
+    },
+    /// Only with the "mintable" extension. The current minter proposes a new minter
+    /// without expiry, same as ProposeMinter. The new minter must accept it.
     UpdateMinter { new_minter: String },
+    /// Only with the "mintable" extension. The current minter proposes a new minter,
+    /// who takes over with AcceptMinter before the proposal expires (never by default)
+    ProposeMinter {
+        new_minter: String,
+        expires: Option<Expiration>,
+    },
+    /// Become the minter, only callable by the proposed minter
+    AcceptMinter {},
+    /// Withdraw the pending minter proposal, only callable by the current minter
+    CancelMinterProposal {},
 
     /// Change sink address, where store all the demurrage tax
     ChangeSinkAddress { address: String }, 
 
     ChangeTaxLevel { amount: u128 },
+
+    /// Create an account class or change its tax level, which replaces the flat tax level
+    /// and the tiers for its members. Must be called by Admin
+    UpdateClass { class: String, tax_level: u128 },
+    /// Put an account into a class, None returns it to the general rate.
+    /// Must be called by Admin
+    AssignClass {
+        address: String,
+        class: Option<String>,
+    },
+    /// Exempt the given part of every balance from demurrage, zero removes the floor.
+    /// Must be called by Admin
+    SetExemptFloor { amount: Uint128 },
+    /// Replace the flat tax level by progressive tiers, an empty list restores it.
+    /// Must be called by Admin
+    UpdateTaxTiers { tiers: Vec<TaxTier> },
+    /// Return the given share of the demurrage collected each period to the accounts
+    /// that spent within it, proportional to their spending. Zero sends everything to
+    /// the sink again. Must be called by Admin
+    SetRebateShare { share: Decimal },
+    /// Claim the spending rebate of a closed period
+    ClaimRebate { period: u64 },
+    /// Move amount from the sender into escrow, it is released to the recipient once
+    /// `at` triggers. Escrowed tokens keep decaying like any balance
+    ScheduleTransfer {
+        recipient: String,
+        amount: Uint128,
+        at: Scheduled,
+    },
+    /// Release up to limit scheduled transfers whose schedule triggered. Callable by anyone
+    ExecuteScheduled { limit: Option<u32> },
+    /// Return the escrowed tokens of a scheduled transfer that was not released yet,
+    /// only callable by its sender
+    CancelScheduled { id: u64 },
+    /// Let the payee charge amount from the sender every interval, the first payment is
+    /// due right away. Payments are taken from the allowance the sender gave the payee
+    CreateSubscription {
+        payee: String,
+        amount: Uint128,
+        interval: Duration,
+        max_payments: Option<u32>,
+    },
+    /// Stop a subscription, callable by its payer or payee
+    CancelSubscription { id: u64 },
+    /// Charge up to limit due subscriptions. A payment without a valid allowance cancels
+    /// its subscription, other failed payments are skipped until the next interval.
+    /// Callable by anyone
+    ProcessSubscriptions { limit: Option<u32> },
+    /// Limit how much the account may send within one period, None removes the limit.
+    /// Must be called by Admin
+    SetRateLimit {
+        address: String,
+        limit: Option<Uint128>,
+    },
+    /// Limit how much all accounts together may send within one period, None removes
+    /// the cap. Must be called by Admin
+    SetGlobalRateLimit { limit: Option<Uint128> },
+    /// Restrict holding the token to members of a cw4 group, None opens it to everyone.
+    /// Must be called by Admin
+    SetMembershipGroup { group: Option<String> },
+    /// Pay the collected demurrage to the members of a cw4 group weighted by their
+    /// weight instead of the sink, None sends it to the sink again. Must be called by Admin
+    SetUbiGroup { group: Option<String> },
+    /// Pay the next members of the current UBI round, starting a new round with the
+    /// collected demurrage if none is in progress. Callable by anyone
+    Distribute { limit: Option<u32> },
+    /// Remove balances worth less than half a display unit from accounts that did not
+    /// spend anything for 30 days, the remainder goes to the sink. Looks at up to limit
+    /// accounts, the start_after attribute of the response continues the scan. Callable by anyone
+    SweepDust {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Block all balance changes of the account. Must be called by Admin or Guardian
+    Freeze { address: String },
+    /// Lift the freeze of an account. Must be called by Admin or Guardian
+    Unfreeze { address: String },
+    /// Stop all transfers, mints and burns. Demurrage keeps accruing while paused and is
+    /// applied with the first period change after Unpause. Must be called by Admin or Guardian
+    Pause {},
+    /// Must be called by Admin or Guardian
+    Unpause {},
+    /// Change the guardian, only callable by the admin
+    UpdateGuardian { guardian: Option<String> },
+
+    /// Propose a new admin without expiry, same as ProposeAdmin. None gives up the
+    /// admin role right away. Only callable by the current admin
+    UpdateAdmin { admin: Option<String> },
+    /// Propose a new admin, who takes over with AcceptAdmin before the proposal
+    /// expires (never by default). Must be called by Admin
+    ProposeAdmin {
+        new_admin: String,
+        expires: Option<Expiration>,
+    },
+    /// Become the admin, only callable by the proposed admin
+    AcceptAdmin {},
+    /// Withdraw the pending admin proposal. Must be called by Admin
+    CancelAdminProposal {},
+    /// Only with a native reserve. Mints tokens to the sender for the reserve coins sent along
+    Deposit {},
+    /// Only with a native reserve. Burns amount tokens from the sender and returns the
+    /// reserve coins for their current worth, minus the redemption fee which goes to the sink
+    Redeem { amount: Uint128 },
+
+    /// Set the reduced tax on locked funds and the unbonding period, enables savings locks.
+    /// Must be called by Admin
+    UpdateSavingsConfig {
+        tax_level: u128,
+        unbonding_period: Duration,
+    },
+    /// Move amount out of the balance into a savings lock for the given term.
+    /// Locked funds only pay the reduced savings tax instead of the demurrage
+    Lock { amount: Uint128, duration: Duration },
+    /// End all locks of the sender whose term is over. Their worth can be claimed
+    /// with ClaimUnlocked after the unbonding period
+    Unlock {},
+    /// Return all unlocked tokens whose unbonding period is over to the balance
+    ClaimUnlocked {},
+
+    /// Register an additional minter or update the limits of an existing one.
+    /// Must be called by Admin
+    AddMinter {
+        minter: String,
+        cap: Option<Uint128>,
+        per_period_limit: Option<Uint128>,
+    },
+    /// Remove an additional minter. Must be called by Admin
+    RemoveMinter { minter: String },
+    /// Add a new hook to be informed of all balance changes. Must be called by Admin
+    AddHook { addr: String },
+    /// Remove a hook. Must be called by Admin
+    RemoveHook { addr: String },
 }
 
 
@@ -150,6 +365,12 @@ pub enum QueryMsg {
     /// Returns who can mint and the hard cap on maximum tokens after minting.
     /// Return type: MinterResponse.
     Minter {},
+    /// Returns the additional minters with their caps and usage. Supports pagination.
+    /// Return type: MintersResponse.
+    Minters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     /// Only with "allowance" extension.
     /// Returns how much spender can use from owner account, 0 if unset.
     /// Return type: AllowanceResponse.
@@ -169,6 +390,15 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Returns all accounts with their balances after demurrage. Supports pagination.
+    /// Return type: BalancesResponse.
+    AllBalances {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the accounts with the largest balances by worth, largest first.
+    /// Return type: BalancesResponse.
+    TopHolders { limit: Option<u32> },
     /// Returns the current demurrage amount (total taxes)
     /// Return type: DemurrageAmountResponse
     DemurrageAmount {}, 
@@ -178,4 +408,116 @@ pub enum QueryMsg {
     /// Returns the current sink account address (where stores most of the distribution)
     /// Return type: SinkAccountResponse 
     SinkAccount {}, 
+    /// Returns the vesting schedules of the address and the amount still locked.
+    /// Return type: VestingResponse
+    Vesting { address: String },
+    /// Returns the demurrage paid, the amounts sent and received over the lifetime of
+    /// the account and when it last spent tokens.
+    /// Return type: AccountStatsResponse
+    AccountStats { address: String },
+    /// Returns the accounts assigned to the class. Supports pagination.
+    /// Return type: AccountsByClassResponse
+    AccountsByClass {
+        class: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the balance of the address split into the part below the exempt floor
+    /// and the part demurrage applies to.
+    /// Return type: TaxableBalanceResponse
+    TaxableBalance { address: String },
+    /// Returns the progressive tax tiers, empty if the flat tax level applies.
+    /// Return type: TaxTiersResponse
+    TaxTiers {},
+    /// Returns the unclaimed spending rebates of the address per closed period.
+    /// Return type: PendingRebateResponse
+    PendingRebate { address: String },
+    /// Returns the average worth of the balance of the address between from and to,
+    /// weighted by time. The worth after each change decays with the demurrage
+    /// until the next change.
+    /// Return type: TwabBalanceResponse
+    TwabBalance {
+        address: String,
+        from: Timestamp,
+        to: Timestamp,
+    },
+    /// Returns the outflow limits of the address and the global cap with their usage
+    /// in the current period.
+    /// Return type: RateLimitResponse
+    RateLimit { address: String },
+    /// Returns the cw4 group whose members may hold the token, if any.
+    /// Return type: MembershipGroupResponse
+    MembershipGroup {},
+    /// Returns the UBI group, the demurrage collected for the next round and the
+    /// round in progress.
+    /// Return type: UbiStatusResponse
+    UbiStatus {},
+    /// Returns the proposed admin and minter that did not accept yet.
+    /// Return type: PendingOwnershipResponse
+    PendingOwnership {},
+    /// Returns the pending scheduled transfers ordered by id, only those of sender if set.
+    /// Return type: ScheduledTransfersResponse
+    Scheduled {
+        sender: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the subscriptions paid by the address ordered by id.
+    /// Return type: SubscriptionsResponse
+    SubscriptionsByPayer {
+        payer: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the subscriptions paid to the address ordered by id.
+    /// Return type: SubscriptionsResponse
+    SubscriptionsByPayee {
+        payee: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Runs the message as if sent by sender without storing anything, to preview the
+    /// resulting balances and the demurrage collected on the way.
+    /// Return type: SimulateResponse
+    Simulate { sender: String, msg: ExecuteMsg },
+    /// Returns the number of transfers, their volume and the number of distinct senders
+    /// and receivers within the given demurrage period.
+    /// Return type: PeriodMetricsResponse
+    PeriodMetrics { period: u64 },
+    /// Returns what the balance of the address will be worth at the given time if
+    /// nothing is spent until then, at the rate of its class or the tax tiers.
+    /// Does not change any state.
+    /// Return type: ProjectedBalanceResponse
+    ProjectedBalance { address: String, at: Timestamp },
+    /// Returns how much of the balance of the address demurrage takes until the given time.
+    /// Return type: TaxDueResponse
+    TaxDue { address: String, until: Timestamp },
+    /// Returns the savings locks of the address with their current worth and the
+    /// pending claims of unlocked tokens.
+    /// Return type: LocksResponse
+    Locks { address: String },
+    /// Return type: SavingsConfigResponse
+    SavingsConfig {},
+    /// Returns the nonce the next permit of the owner must use.
+    /// Return type: NonceResponse
+    Nonce { owner: String },
+    /// Only with a native reserve.
+    /// Returns the reserve configuration and the amount of native coins held.
+    /// Return type: ReserveResponse
+    Reserve {},
+    /// Returns all frozen accounts. Supports pagination.
+    /// Return type: FrozenAccountsResponse
+    FrozenAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Return type: PausedResponse
+    Paused {},
+    /// Return AdminResponse
+    Guardian {},
+    /// Return AdminResponse
+    Admin {},
+    /// Shows all registered balance hooks.
+    /// Return type: HooksResponse
+    Hooks {},
 }
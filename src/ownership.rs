@@ -0,0 +1,157 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cw_utils::Expiration;
+
+use crate::error::ContractError;
+use crate::query::PendingOwnershipResponse;
+use crate::state::{PendingOwner, ADMIN, PENDING_ADMIN, PENDING_MINTER, TOKEN_INFO};
+
+fn assert_minter(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    let config = TOKEN_INFO.load(deps.storage)?;
+    match config.mint {
+        Some(mint) if &mint.minter == sender => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+fn new_proposal(
+    deps: Deps,
+    env: &Env,
+    new_owner: &str,
+    expires: Option<Expiration>,
+) -> Result<PendingOwner, ContractError> {
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::OwnershipProposalExpired {});
+    }
+    Ok(PendingOwner {
+        new_owner: deps.api.addr_validate(new_owner)?,
+        expires,
+    })
+}
+
+/// The pending proposal, if it was made for the sender and did not expire yet
+fn accepted_proposal(
+    pending: Option<PendingOwner>,
+    env: &Env,
+    sender: &Addr,
+) -> Result<PendingOwner, ContractError> {
+    let pending = pending.ok_or(ContractError::NoPendingOwnership {})?;
+    if &pending.new_owner != sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if pending.expires.is_expired(&env.block) {
+        return Err(ContractError::OwnershipProposalExpired {});
+    }
+    Ok(pending)
+}
+
+pub fn execute_propose_minter(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_minter: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    assert_minter(deps.as_ref(), &info.sender)?;
+
+    let pending = new_proposal(deps.as_ref(), &env, &new_minter, expires)?;
+    PENDING_MINTER.save(deps.storage, &pending)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_minter")
+        .add_attribute("new_minter", new_minter)
+        .add_attribute("expires", pending.expires.to_string()))
+}
+
+pub fn execute_accept_minter(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending = accepted_proposal(PENDING_MINTER.may_load(deps.storage)?, &env, &info.sender)?;
+
+    let mut config = TOKEN_INFO.load(deps.storage)?;
+    let mint = config.mint.as_mut().ok_or(ContractError::Unauthorized {})?;
+    mint.minter = pending.new_owner;
+    TOKEN_INFO.save(deps.storage, &config)?;
+    PENDING_MINTER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_minter")
+        .add_attribute("new_minter", info.sender))
+}
+
+pub fn execute_cancel_minter_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_minter(deps.as_ref(), &info.sender)?;
+    if PENDING_MINTER.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoPendingOwnership {});
+    }
+    PENDING_MINTER.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "cancel_minter_proposal"))
+}
+
+pub fn execute_propose_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_admin: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let pending = new_proposal(deps.as_ref(), &env, &new_admin, expires)?;
+    PENDING_ADMIN.save(deps.storage, &pending)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_admin")
+        .add_attribute("new_admin", new_admin)
+        .add_attribute("expires", pending.expires.to_string()))
+}
+
+pub fn execute_accept_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending = accepted_proposal(PENDING_ADMIN.may_load(deps.storage)?, &env, &info.sender)?;
+
+    PENDING_ADMIN.remove(deps.storage);
+    ADMIN.set(deps, Some(pending.new_owner))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_admin")
+        .add_attribute("new_admin", info.sender))
+}
+
+/// Leave the contract without admin, a pending proposal can not be accepted anymore
+pub fn execute_renounce_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    PENDING_ADMIN.remove(deps.storage);
+    ADMIN.set(deps, None)?;
+
+    Ok(Response::new().add_attribute("action", "renounce_admin"))
+}
+
+pub fn execute_cancel_admin_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    if PENDING_ADMIN.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoPendingOwnership {});
+    }
+    PENDING_ADMIN.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "cancel_admin_proposal"))
+}
+
+pub fn query_pending_ownership(deps: Deps) -> StdResult<PendingOwnershipResponse> {
+    Ok(PendingOwnershipResponse {
+        admin: PENDING_ADMIN.may_load(deps.storage)?,
+        minter: PENDING_MINTER.may_load(deps.storage)?,
+    })
+}
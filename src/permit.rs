@@ -0,0 +1,132 @@
+use bech32::{FromBase32, ToBase32, Variant};
+use ripemd160::Ripemd160;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use cosmwasm_std::{
+    to_vec, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+};
+
+use crate::contract::{change_period, prepare_balance_hooks, transfer_tokens};
+use crate::error::ContractError;
+use crate::freeze::assert_can_transfer;
+use crate::membership::assert_members;
+use crate::msg::Permit;
+use crate::query::NonceResponse;
+use crate::state::{NONCES, STATE};
+use crate::stats::settle_tiered_tax;
+
+/// The document whose sha256 hash is signed for a permit. Binding it to the chain
+/// and contract prevents replaying the permit on another deployment.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PermitSignDoc {
+    pub chain_id: String,
+    pub contract: String,
+    pub permit: Permit,
+}
+
+impl PermitSignDoc {
+    pub fn hash(&self) -> StdResult<Vec<u8>> {
+        Ok(Sha256::digest(&to_vec(self)?).to_vec())
+    }
+}
+
+pub fn execute_transfer_with_permit(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    permit: Permit,
+    signature: Binary,
+    pubkey: Binary,
+) -> Result<Response, ContractError> {
+    if permit.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    if permit.expires.is_expired(&env.block) {
+        return Err(ContractError::PermitExpired {});
+    }
+
+    let owner_addr = deps.api.addr_validate(&permit.owner)?;
+    let rcpt_addr = deps.api.addr_validate(&permit.recipient)?;
+    assert_can_transfer(deps.storage, &[&owner_addr, &rcpt_addr])?;
+    assert_members(deps.as_ref(), &[&owner_addr, &rcpt_addr])?;
+
+    // every nonce can only be used once, in order
+    let expected = NONCES.may_load(deps.storage, &owner_addr)?.unwrap_or_default();
+    if permit.nonce != expected {
+        return Err(ContractError::InvalidNonce { expected });
+    }
+    if !pubkey_matches_address(&pubkey, &permit.owner) {
+        return Err(ContractError::PubkeyMismatch {});
+    }
+    let sign_doc = PermitSignDoc {
+        chain_id: env.block.chain_id.clone(),
+        contract: env.contract.address.to_string(),
+        permit: permit.clone(),
+    };
+    let verified = deps
+        .api
+        .secp256k1_verify(&sign_doc.hash()?, &signature, &pubkey)
+        .map_err(|_| ContractError::InvalidSignature {})?;
+    if !verified {
+        return Err(ContractError::InvalidSignature {});
+    }
+    NONCES.save(deps.storage, &owner_addr, &(expected + 1))?;
+
+    let mut state = STATE.load(deps.storage)?;
+    let period_res = change_period(&mut deps, env.clone(), &mut state)?;
+
+    let mut diffs = settle_tiered_tax(deps.storage, &env.block, &[&owner_addr, &rcpt_addr])?;
+    diffs.extend(transfer_tokens(
+        deps.storage,
+        &env.block,
+        &state,
+        &owner_addr,
+        &rcpt_addr,
+        permit.amount,
+    )?);
+    let hooks = prepare_balance_hooks(deps.storage, diffs)?;
+
+    let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_attribute("action", "transfer_with_permit")
+        .add_attribute("from", permit.owner)
+        .add_attribute("to", permit.recipient)
+        .add_attribute("by", info.sender)
+        .add_attribute("amount", permit.amount)
+        .add_attribute("nonce", permit.nonce.to_string());
+    Ok(res)
+}
+
+/// Checks that the bech32 address is derived from the compressed secp256k1 public key,
+/// the same way the chain derives account addresses
+pub fn pubkey_matches_address(pubkey: &[u8], address: &str) -> bool {
+    let (hrp, data, variant) = match bech32::decode(address) {
+        Ok(decoded) => decoded,
+        Err(_) => return false,
+    };
+    let raw = match Vec::<u8>::from_base32(&data) {
+        Ok(raw) => raw,
+        Err(_) => return false,
+    };
+    variant == Variant::Bech32 && !hrp.is_empty() && raw == pubkey_to_raw_address(pubkey)
+}
+
+/// ripemd160(sha256(pubkey)), the raw account address of a public key
+pub fn pubkey_to_raw_address(pubkey: &[u8]) -> Vec<u8> {
+    Ripemd160::digest(&Sha256::digest(pubkey)).to_vec()
+}
+
+/// Encode the account address of a public key with the given bech32 prefix
+pub fn pubkey_to_address(pubkey: &[u8], prefix: &str) -> StdResult<String> {
+    bech32::encode(prefix, pubkey_to_raw_address(pubkey).to_base32(), Variant::Bech32)
+        .map_err(|e| StdError::generic_err(e.to_string()))
+}
+
+pub fn query_nonce(deps: Deps, owner: String) -> StdResult<NonceResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let nonce = NONCES.may_load(deps.storage, &owner_addr)?.unwrap_or_default();
+    Ok(NonceResponse { nonce })
+}
@@ -0,0 +1,55 @@
+use cosmwasm_std::{Deps, Env, StdError, StdResult, Timestamp, Uint128};
+
+use crate::contract::{decay_by, get_minutes_delta};
+use crate::query::{ProjectedBalanceResponse, TaxDueResponse};
+use crate::state::{State, STATE, balances};
+use crate::tiers::account_worth;
+
+/// Demurrage modifier apply_demurrage would cache if it ran at the given time.
+/// Tax level changes take effect immediately, so the current level is used for the
+/// whole span.
+pub fn projected_demurrage_amount(state: &State, at: Timestamp) -> u128 {
+    let period_count = get_minutes_delta(at, state.demurrage_timestamp);
+    if period_count == 0 {
+        return state.demurrage_amount;
+    }
+    decay_by(state.demurrage_amount, state.tax_level, period_count)
+}
+
+/// Worth of the balance now and at the given time, if nothing is spent until then
+fn project(deps: Deps, env: &Env, address: String, at: Timestamp) -> StdResult<(Uint128, Uint128)> {
+    if at < env.block.time {
+        return Err(StdError::generic_err("Projection time must not be in the past"));
+    }
+    let address = deps.api.addr_validate(&address)?;
+    let balance = balances()
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let state = STATE.load(deps.storage)?;
+
+    let now = account_worth(deps.storage, &state, &address, balance, env.block.time)?;
+    let then = account_worth(deps.storage, &state, &address, balance, at)?;
+    Ok((now, then))
+}
+
+pub fn query_projected_balance(
+    deps: Deps,
+    env: Env,
+    address: String,
+    at: Timestamp,
+) -> StdResult<ProjectedBalanceResponse> {
+    let (_, balance) = project(deps, &env, address, at)?;
+    Ok(ProjectedBalanceResponse { balance })
+}
+
+pub fn query_tax_due(
+    deps: Deps,
+    env: Env,
+    address: String,
+    until: Timestamp,
+) -> StdResult<TaxDueResponse> {
+    let (now, then) = project(deps, &env, address, until)?;
+    Ok(TaxDueResponse {
+        tax_due: now - then,
+    })
+}
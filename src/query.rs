@@ -1,7 +1,11 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Uint128};
+use cosmwasm_std::{Decimal, Timestamp, Uint128};
+use cw_controllers::Claim;
+use cw_utils::{Duration, Expiration, Scheduled};
+
+use crate::state::{PendingOwner, Subscription, TaxTier, UbiRound, Vesting};
 
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
@@ -14,7 +18,213 @@ pub struct SinkAddressResponse {
     pub sink_address: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MinterInfo {
+    pub minter: String,
+    pub cap: Option<Uint128>,
+    pub minted: Uint128,
+    pub per_period_limit: Option<Uint128>,
+    /// amount minted in the current period
+    pub period_minted: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct MintersResponse {
+    pub minters: Vec<MinterInfo>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct TaxLevelResponse {
     pub tax_level: Uint128,
 }
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ReserveResponse {
+    pub denom: String,
+    pub ratio: Decimal,
+    pub redemption_fee: Decimal,
+    /// native coins currently held as reserve
+    pub reserve: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct NonceResponse {
+    pub nonce: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct VestingResponse {
+    pub schedules: Vec<Vesting>,
+    /// amount that cannot be spent yet
+    pub locked: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct FrozenAccountsResponse {
+    pub accounts: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct PausedResponse {
+    pub paused: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct LockInfo {
+    /// balance units the lock would return if unlocked now
+    pub amount: Uint128,
+    pub locked_until: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct LocksResponse {
+    pub locks: Vec<LockInfo>,
+    pub claims: Vec<Claim>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SavingsConfigResponse {
+    pub tax_level: u128,
+    pub unbonding_period: Duration,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct ProjectedBalanceResponse {
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct TaxDueResponse {
+    pub tax_due: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AccountBalance {
+    pub address: String,
+    /// balance after the demurrage accrued up to the query
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct BalancesResponse {
+    pub balances: Vec<AccountBalance>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct AccountStatsResponse {
+    /// demurrage taken from the balance, which flows to the sink
+    pub tax_paid: Uint128,
+    pub sent: Uint128,
+    pub received: Uint128,
+    pub last_spend: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct PeriodMetricsResponse {
+    pub period: u64,
+    pub transfers: u64,
+    pub volume: Uint128,
+    pub unique_senders: u64,
+    pub unique_receivers: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct TaxTiersResponse {
+    pub tiers: Vec<TaxTier>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct AccountsByClassResponse {
+    pub accounts: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct TaxableBalanceResponse {
+    pub balance: Uint128,
+    pub exempt: Uint128,
+    pub taxable: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RebateInfo {
+    pub period: u64,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct PendingRebateResponse {
+    pub rebates: Vec<RebateInfo>,
+    pub total: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct TwabBalanceResponse {
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct RateLimitResponse {
+    pub limit: Option<Uint128>,
+    pub used: Uint128,
+    pub global_limit: Option<Uint128>,
+    pub global_used: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct MembershipGroupResponse {
+    pub group: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct UbiStatusResponse {
+    pub group: Option<String>,
+    /// collected for the next round
+    pub pending: Uint128,
+    pub round: Option<UbiRound>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct PendingOwnershipResponse {
+    pub admin: Option<PendingOwner>,
+    pub minter: Option<PendingOwner>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct SimulateResponse {
+    /// why the message would fail, nothing else is set in that case
+    pub error: Option<String>,
+    /// accounts whose balance changes, with their balance afterwards
+    pub balances: Vec<AccountBalance>,
+    /// demurrage modifier after the message
+    pub demurrage_amount: Uint128,
+    pub current_period: u64,
+    /// demurrage credited to the sink for the periods the message closes, without the
+    /// parts kept for spending rebates or basic income
+    pub redistributed: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ScheduledTransferInfo {
+    pub id: u64,
+    pub sender: String,
+    pub recipient: String,
+    /// current worth of the escrowed tokens
+    pub amount: Uint128,
+    pub at: Scheduled,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct ScheduledTransfersResponse {
+    pub transfers: Vec<ScheduledTransferInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SubscriptionInfo {
+    pub id: u64,
+    pub subscription: Subscription,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct SubscriptionsResponse {
+    pub subscriptions: Vec<SubscriptionInfo>,
+}
@@ -0,0 +1,123 @@
+use cosmwasm_std::{
+    Addr, BlockInfo, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Uint128,
+};
+
+use crate::contract::actual_period;
+use crate::error::ContractError;
+use crate::query::RateLimitResponse;
+use crate::state::{AccountOutflow, ADMIN, GLOBAL_OUTFLOW, GLOBAL_RATE_LIMIT, OUTFLOW, RATE_LIMITS, STATE};
+
+/// Period of the block time. Limits follow the clock rather than the stored period,
+/// which only moves when change_period runs
+fn outflow_period(storage: &dyn Storage, block: &BlockInfo) -> StdResult<u64> {
+    let state = STATE.load(storage)?;
+    Ok(actual_period(block.time, &state) as u64)
+}
+
+/// Outflow within the period, usage of earlier periods does not count
+fn period_used(outflow: Option<AccountOutflow>, period: u64) -> Uint128 {
+    outflow
+        .filter(|outflow| outflow.period == period)
+        .map(|outflow| outflow.used)
+        .unwrap_or_default()
+}
+
+/// Count amount against the outflow limits of the sender and the global cap of the
+/// current period. Fails with the allowance left if either would be exceeded.
+pub fn consume_outflow(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    from: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let account_limit = RATE_LIMITS.may_load(storage, from)?;
+    let global_limit = GLOBAL_RATE_LIMIT.may_load(storage)?;
+    if account_limit.is_none() && global_limit.is_none() {
+        return Ok(());
+    }
+    let period = outflow_period(storage, block)?;
+    let used = period_used(OUTFLOW.may_load(storage, from)?, period);
+    let global_used = period_used(GLOBAL_OUTFLOW.may_load(storage)?, period);
+    let remaining = match (account_limit, global_limit) {
+        (Some(limit), Some(global)) => {
+            limit.saturating_sub(used).min(global.saturating_sub(global_used))
+        }
+        (Some(limit), None) => limit.saturating_sub(used),
+        (None, global) => global.unwrap_or_default().saturating_sub(global_used),
+    };
+    if amount > remaining {
+        return Err(ContractError::RateLimited { remaining });
+    }
+
+    if account_limit.is_some() {
+        let outflow = AccountOutflow {
+            period,
+            used: used + amount,
+        };
+        OUTFLOW.save(storage, from, &outflow)?;
+    }
+    if global_limit.is_some() {
+        let outflow = AccountOutflow {
+            period,
+            used: global_used + amount,
+        };
+        GLOBAL_OUTFLOW.save(storage, &outflow)?;
+    }
+    Ok(())
+}
+
+/// Set or remove the per period outflow limit of an account. Must be called by Admin
+pub fn execute_set_rate_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    limit: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
+
+    match limit {
+        Some(limit) => RATE_LIMITS.save(deps.storage, &addr, &limit)?,
+        None => {
+            RATE_LIMITS.remove(deps.storage, &addr);
+            OUTFLOW.remove(deps.storage, &addr);
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_rate_limit")
+        .add_attribute("address", address)
+        .add_attribute("limit", limit.map_or_else(|| "None".to_string(), |l| l.to_string())))
+}
+
+/// Set or remove the per period outflow cap over all accounts. Must be called by Admin
+pub fn execute_set_global_rate_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    limit: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    match limit {
+        Some(limit) => GLOBAL_RATE_LIMIT.save(deps.storage, &limit)?,
+        None => {
+            GLOBAL_RATE_LIMIT.remove(deps.storage);
+            GLOBAL_OUTFLOW.remove(deps.storage);
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_global_rate_limit")
+        .add_attribute("limit", limit.map_or_else(|| "None".to_string(), |l| l.to_string())))
+}
+
+pub fn query_rate_limit(deps: Deps, env: Env, address: String) -> StdResult<RateLimitResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let period = outflow_period(deps.storage, &env.block)?;
+    Ok(RateLimitResponse {
+        limit: RATE_LIMITS.may_load(deps.storage, &addr)?,
+        used: period_used(OUTFLOW.may_load(deps.storage, &addr)?, period),
+        global_limit: GLOBAL_RATE_LIMIT.may_load(deps.storage)?,
+        global_used: period_used(GLOBAL_OUTFLOW.may_load(deps.storage)?, period),
+    })
+}
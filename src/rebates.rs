@@ -0,0 +1,158 @@
+use cosmwasm_std::{
+    Addr, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage, Uint128,
+};
+
+use crate::contract::{prepare_balance_hooks, to_base_amount};
+use crate::error::ContractError;
+use crate::freeze::assert_can_transfer;
+use crate::hook::BalanceDiff;
+use crate::query::{PendingRebateResponse, RebateInfo};
+use crate::state::{
+    balances, ADMIN, PERIOD_METRICS, REBATE_CLAIMED, REBATE_POOLS, REBATE_SHARE, SPENDING,
+    STATE,
+};
+use crate::stats::{record_balance_changes, settle_tiered_tax};
+
+/// Add to what the account spent within the period
+pub fn record_spending(
+    storage: &mut dyn Storage,
+    period: u64,
+    addr: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    SPENDING.update(storage, (addr, period), |spent| -> StdResult<_> {
+        Ok(spent.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+}
+
+/// Keep the rebate share of the demurrage collected for the closed period in its
+/// pool and return what is left for the sink. Nothing is kept if nobody spent
+/// anything in that period.
+pub fn reserve_rebates(
+    storage: &mut dyn Storage,
+    period: u64,
+    distribution: u128,
+) -> StdResult<u128> {
+    let share = match REBATE_SHARE.may_load(storage)? {
+        Some(share) => share,
+        None => return Ok(distribution),
+    };
+    let volume = PERIOD_METRICS
+        .may_load(storage, period)?
+        .unwrap_or_default()
+        .volume;
+    if volume.is_zero() {
+        return Ok(distribution);
+    }
+
+    let rebate = Uint128::from(distribution) * share;
+    REBATE_POOLS.save(storage, period, &rebate)?;
+    Ok(distribution - rebate.u128())
+}
+
+/// Rebate of the account for a closed period, proportional to its part of the volume
+fn rebate_for(storage: &dyn Storage, period: u64, spent: Uint128) -> StdResult<Uint128> {
+    let pool = REBATE_POOLS.may_load(storage, period)?.unwrap_or_default();
+    let volume = PERIOD_METRICS
+        .may_load(storage, period)?
+        .unwrap_or_default()
+        .volume;
+    if volume.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    Ok(pool.multiply_ratio(spent, volume))
+}
+
+/// Set the share of the collected demurrage returned as spending rebates,
+/// zero sends everything to the sink again. Must be called by Admin
+pub fn execute_set_rebate_share(
+    deps: DepsMut,
+    info: MessageInfo,
+    share: Decimal,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    if share > Decimal::one() {
+        return Err(ContractError::InvalidRebateShare {});
+    }
+
+    if share.is_zero() {
+        REBATE_SHARE.remove(deps.storage);
+    } else {
+        REBATE_SHARE.save(deps.storage, &share)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_rebate_share")
+        .add_attribute("share", share.to_string()))
+}
+
+/// Credits the rebate of the sender for a closed period, converted to balance units at
+/// the current modifier. Claims never take more than is left in the pool.
+pub fn execute_claim_rebate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    period: u64,
+) -> Result<Response, ContractError> {
+    assert_can_transfer(deps.storage, &[&info.sender])?;
+
+    // the pool of a period is only known once it is closed
+    let state = STATE.load(deps.storage)?;
+    if period >= state.current_period {
+        return Err(ContractError::NoRebate {});
+    }
+    let spent = SPENDING
+        .may_load(deps.storage, (&info.sender, period))?
+        .ok_or(ContractError::NoRebate {})?;
+    let pool = REBATE_POOLS.may_load(deps.storage, period)?.unwrap_or_default();
+    let claimed = REBATE_CLAIMED.may_load(deps.storage, period)?.unwrap_or_default();
+    let amount = rebate_for(deps.storage, period, spent)?.min(pool - claimed);
+    if amount.is_zero() {
+        return Err(ContractError::NoRebate {});
+    }
+    SPENDING.remove(deps.storage, (&info.sender, period));
+    REBATE_CLAIMED.save(deps.storage, period, &(claimed + amount))?;
+    let settled = settle_tiered_tax(deps.storage, &env.block, &[&info.sender])?;
+
+    let base_amount = Uint128::from(to_base_amount(amount.u128(), state.demurrage_amount));
+    let balance = balances().update(
+        deps.storage,
+        &info.sender,
+        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + base_amount) },
+    )?;
+    let diffs = vec![BalanceDiff::credited(&info.sender, balance, base_amount)];
+    record_balance_changes(deps.storage, &env.block, &diffs)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
+
+    let res = Response::new()
+        .add_submessages(hooks)
+        .add_attribute("action", "claim_rebate")
+        .add_attribute("to", info.sender)
+        .add_attribute("period", period.to_string())
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+/// Unclaimed rebates of all closed periods the account spent in
+pub fn query_pending_rebate(deps: Deps, address: String) -> StdResult<PendingRebateResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let current_period = STATE.load(deps.storage)?.current_period;
+
+    let mut rebates = vec![];
+    for item in SPENDING
+        .prefix(&addr)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (period, spent) = item?;
+        if period >= current_period {
+            break;
+        }
+        let amount = rebate_for(deps.storage, period, spent)?;
+        if !amount.is_zero() {
+            rebates.push(RebateInfo { period, amount });
+        }
+    }
+    let total = rebates.iter().map(|rebate| rebate.amount).sum();
+    Ok(PendingRebateResponse { rebates, total })
+}
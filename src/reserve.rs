@@ -0,0 +1,163 @@
+use cosmwasm_std::{
+    coins, BankMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    Uint128,
+};
+use cw_utils::must_pay;
+
+use crate::contract::{change_period, from_base_amount, prepare_balance_hooks, to_base_amount};
+use crate::error::ContractError;
+use crate::freeze::assert_can_transfer;
+use crate::membership::assert_members;
+use crate::hook::BalanceDiff;
+use crate::query::ReserveResponse;
+use crate::ratelimit::consume_outflow;
+use crate::stats::{record_balance_changes, settle_tiered_tax};
+use crate::vesting::debit_balance;
+use crate::state::{RESERVE, RESERVE_CONFIG, STATE, TOKEN_INFO, balances};
+
+pub fn execute_deposit(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let reserve = RESERVE_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoReserve {})?;
+    let paid = must_pay(&info, &reserve.denom)?;
+    assert_can_transfer(deps.storage, &[&info.sender])?;
+    assert_members(deps.as_ref(), &[&info.sender])?;
+
+    let amount = paid * reserve.ratio;
+    if amount == Uint128::zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    // update supply and enforce cap
+    let mut config = TOKEN_INFO.load(deps.storage)?;
+    config.total_supply += amount;
+    if let Some(limit) = config.get_cap() {
+        if config.total_supply > limit {
+            return Err(ContractError::CannotExceedCap {});
+        }
+    }
+    TOKEN_INFO.save(deps.storage, &config)?;
+
+    RESERVE.update(deps.storage, |held| -> StdResult<_> {
+        Ok(held.checked_add(paid)?)
+    })?;
+
+    let mut state = STATE.load(deps.storage)?;
+    let period_res = change_period(&mut deps, env.clone(), &mut state)?;
+    let base_value = Uint128::from(to_base_amount(amount.u128(), state.demurrage_amount));
+    let settled = settle_tiered_tax(deps.storage, &env.block, &[&info.sender])?;
+
+    let balance = balances().update(
+        deps.storage,
+        &info.sender,
+        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + base_value) },
+    )?;
+
+    let diffs = vec![BalanceDiff::credited(&info.sender, balance, base_value)];
+    record_balance_changes(deps.storage, &env.block, &diffs)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
+
+    let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_attribute("action", "deposit")
+        .add_attribute("to", info.sender)
+        .add_attribute("paid", paid)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+/// Burns amount balance units of the sender and pays out the reserve coins for their
+/// worth after the demurrage up to the current period
+pub fn execute_redeem(
+    mut deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount == Uint128::zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let reserve = RESERVE_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoReserve {})?;
+    assert_can_transfer(deps.storage, &[&info.sender])?;
+
+    let mut state = STATE.load(deps.storage)?;
+    let period_res = change_period(&mut deps, _env.clone(), &mut state)?;
+    let sink_addr = deps.api.addr_validate(&state.sink_address)?;
+
+    // the fee stays in circulation at the sink, only the rest is burned and paid out
+    let fee = amount * reserve.redemption_fee;
+    if fee > Uint128::zero() {
+        assert_members(deps.as_ref(), &[&sink_addr])?;
+    }
+    let burned = amount - fee;
+    let worth = from_base_amount(burned, state.demurrage_amount);
+    let payout = worth.multiply_ratio(Decimal::one().atomics(), reserve.ratio.atomics());
+    if payout == Uint128::zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    RESERVE.update(deps.storage, |held| -> Result<_, ContractError> {
+        held.checked_sub(payout)
+            .map_err(|_| ContractError::InsufficientReserve {})
+    })?;
+
+    let settled = settle_tiered_tax(deps.storage, &_env.block, &[&info.sender])?;
+
+    // lower balance
+    let balance = debit_balance(deps.storage, &_env.block, &info.sender, amount)?;
+    consume_outflow(deps.storage, &_env.block, &info.sender, amount)?;
+    let mut diffs = vec![BalanceDiff::debited(&info.sender, balance, amount)];
+
+    if fee > Uint128::zero() {
+        let sink_balance = balances().update(
+            deps.storage,
+            &sink_addr,
+            |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + fee) },
+        )?;
+        diffs.push(BalanceDiff::credited(&sink_addr, sink_balance, fee));
+    }
+
+    // reduce total_supply by the worth, like deposits and mints add it
+    TOKEN_INFO.update(deps.storage, |mut meta| -> StdResult<_> {
+        meta.total_supply = meta.total_supply.checked_sub(worth)?;
+        Ok(meta)
+    })?;
+
+    record_balance_changes(deps.storage, &_env.block, &diffs)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
+
+    let res = Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(payout.u128(), reserve.denom),
+        })
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_attribute("action", "redeem")
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", amount)
+        .add_attribute("fee", fee)
+        .add_attribute("payout", payout);
+    Ok(res)
+}
+
+pub fn query_reserve(deps: Deps) -> StdResult<ReserveResponse> {
+    let config = RESERVE_CONFIG
+        .may_load(deps.storage)?
+        .ok_or_else(|| StdError::generic_err("Token is not backed by a native reserve"))?;
+    let reserve = RESERVE.may_load(deps.storage)?.unwrap_or_default();
+    Ok(ReserveResponse {
+        denom: config.denom,
+        ratio: config.ratio,
+        redemption_fee: config.redemption_fee,
+        reserve,
+    })
+}
@@ -0,0 +1,239 @@
+use cosmwasm_std::{
+    BlockInfo, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128,
+};
+use cw_utils::Duration;
+
+use crate::contract::{change_period, decay_by, from_base_amount, prepare_balance_hooks, to_base_amount};
+use crate::error::ContractError;
+use crate::freeze::assert_can_transfer;
+use crate::hook::BalanceDiff;
+use crate::query::{LockInfo, LocksResponse, SavingsConfigResponse};
+use crate::ratelimit::consume_outflow;
+use crate::stats::{record_balance_changes, settle_tiered_tax};
+use crate::state::{
+    SavingsConfig, SavingsLock, State, ADMIN, SAVINGS_CLAIMS, SAVINGS_CONFIG,
+    SAVINGS_LOCKS, STATE, balances,
+};
+use crate::vesting::debit_balance;
+
+/// Balance units the lock is worth at the given block, after the reduced tax of the
+/// savings config was taken from its value for every minute it was locked.
+/// The part above the locked amount is paid by the sink on unlock, as far as it can.
+pub fn lock_balance(
+    lock: &SavingsLock,
+    config: &SavingsConfig,
+    state: &State,
+    block: &BlockInfo,
+) -> Uint128 {
+    lock.amount + savings_bonus(lock, config, state, block)
+}
+
+/// Balance units of the demurrage the reduced savings tax spared the lock
+fn savings_bonus(
+    lock: &SavingsLock,
+    config: &SavingsConfig,
+    state: &State,
+    block: &BlockInfo,
+) -> Uint128 {
+    let minutes = (block.time.seconds() - lock.since.seconds()) / 60;
+    let value = decay_by(lock.value.u128(), config.tax_level, minutes);
+    let worth = from_base_amount(lock.amount, state.demurrage_amount).u128();
+    Uint128::from(to_base_amount(value.saturating_sub(worth), state.demurrage_amount))
+}
+
+pub fn execute_update_savings_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    tax_level: u128,
+    unbonding_period: Duration,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let state = STATE.load(deps.storage)?;
+    if tax_level > state.tax_level {
+        return Err(ContractError::InvalidSavingsTax {});
+    }
+    SAVINGS_CONFIG.save(
+        deps.storage,
+        &SavingsConfig {
+            tax_level,
+            unbonding_period,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_savings_config")
+        .add_attribute("tax_level", tax_level.to_string()))
+}
+
+pub fn execute_lock(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    duration: Duration,
+) -> Result<Response, ContractError> {
+    if amount == Uint128::zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    SAVINGS_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::SavingsDisabled {})?;
+    assert_can_transfer(deps.storage, &[&info.sender])?;
+
+    let mut state = STATE.load(deps.storage)?;
+    let period_res = change_period(&mut deps, env.clone(), &mut state)?;
+
+    let settled = settle_tiered_tax(deps.storage, &env.block, &[&info.sender])?;
+    let balance = debit_balance(deps.storage, &env.block, &info.sender, amount)?;
+    consume_outflow(deps.storage, &env.block, &info.sender, amount)?;
+    let lock = SavingsLock {
+        amount,
+        value: from_base_amount(amount, state.demurrage_amount),
+        since: env.block.time,
+        locked_until: duration.after(&env.block),
+    };
+    SAVINGS_LOCKS.update(deps.storage, &info.sender, |locks| -> StdResult<_> {
+        let mut locks = locks.unwrap_or_default();
+        locks.push(lock.clone());
+        Ok(locks)
+    })?;
+
+    let diffs = vec![BalanceDiff::debited(&info.sender, balance, amount)];
+    record_balance_changes(deps.storage, &env.block, &diffs)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
+
+    let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_attribute("action", "lock")
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", amount)
+        .add_attribute("locked_until", lock.locked_until.to_string());
+    Ok(res)
+}
+
+/// Ends every lock of the sender whose term is over and creates a claim for the locked
+/// amount and the savings bonus, which can be collected after the unbonding period.
+/// The bonus moves out of the sink balance, capped by what the sink holds.
+pub fn execute_unlock(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = SAVINGS_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::SavingsDisabled {})?;
+
+    let mut state = STATE.load(deps.storage)?;
+    // the bonus moves out of the sink balance
+    let sink_addr = deps.api.addr_validate(&state.sink_address)?;
+    assert_can_transfer(deps.storage, &[&info.sender, &sink_addr])?;
+    let period_res = change_period(&mut deps, env.clone(), &mut state)?;
+
+    let locks = SAVINGS_LOCKS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let (matured, waiting): (Vec<_>, Vec<_>) = locks
+        .into_iter()
+        .partition(|lock| lock.locked_until.is_expired(&env.block));
+    if matured.is_empty() {
+        return Err(ContractError::NothingToUnlock {});
+    }
+
+    let locked: Uint128 = matured.iter().map(|lock| lock.amount).sum();
+    let bonus: Uint128 = matured
+        .iter()
+        .map(|lock| savings_bonus(lock, &config, &state, &env.block))
+        .sum();
+    if waiting.is_empty() {
+        SAVINGS_LOCKS.remove(deps.storage, &info.sender);
+    } else {
+        SAVINGS_LOCKS.save(deps.storage, &info.sender, &waiting)?;
+    }
+
+    let sink_balance = balances().may_load(deps.storage, &sink_addr)?.unwrap_or_default();
+    let bonus = bonus.min(sink_balance);
+    let mut hooks = vec![];
+    if !bonus.is_zero() {
+        let sink_balance = sink_balance - bonus;
+        balances().save(deps.storage, &sink_addr, &sink_balance)?;
+        let diffs = vec![BalanceDiff::debited(&sink_addr, sink_balance, bonus)];
+        record_balance_changes(deps.storage, &env.block, &diffs)?;
+        hooks = prepare_balance_hooks(deps.storage, diffs)?;
+    }
+
+    let amount = locked + bonus;
+    let release_at = config.unbonding_period.after(&env.block);
+    SAVINGS_CLAIMS.create_claim(deps.storage, &info.sender, amount, release_at)?;
+
+    let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_attribute("action", "unlock")
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", amount)
+        .add_attribute("release_at", release_at.to_string());
+    Ok(res)
+}
+
+pub fn execute_claim_unlocked(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_can_transfer(deps.storage, &[&info.sender])?;
+
+    let amount = SAVINGS_CLAIMS.claim_tokens(deps.storage, &info.sender, &env.block, None)?;
+    if amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    let settled = settle_tiered_tax(deps.storage, &env.block, &[&info.sender])?;
+
+    let balance = balances().update(
+        deps.storage,
+        &info.sender,
+        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
+    )?;
+    let diffs = vec![BalanceDiff::credited(&info.sender, balance, amount)];
+    record_balance_changes(deps.storage, &env.block, &diffs)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
+
+    let res = Response::new()
+        .add_submessages(hooks)
+        .add_attribute("action", "claim_unlocked")
+        .add_attribute("to", info.sender)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+pub fn query_locks(deps: Deps, env: Env, address: String) -> StdResult<LocksResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let locks = match SAVINGS_CONFIG.may_load(deps.storage)? {
+        Some(config) => {
+            let state = STATE.load(deps.storage)?;
+            SAVINGS_LOCKS
+                .may_load(deps.storage, &addr)?
+                .unwrap_or_default()
+                .iter()
+                .map(|lock| LockInfo {
+                    amount: lock_balance(lock, &config, &state, &env.block),
+                    locked_until: lock.locked_until,
+                })
+                .collect()
+        }
+        None => vec![],
+    };
+    let claims = SAVINGS_CLAIMS.query_claims(deps, &addr)?.claims;
+    Ok(LocksResponse { locks, claims })
+}
+
+pub fn query_savings_config(deps: Deps) -> StdResult<SavingsConfigResponse> {
+    let config = SAVINGS_CONFIG
+        .may_load(deps.storage)?
+        .ok_or_else(|| StdError::generic_err("Savings locks are not enabled"))?;
+    Ok(SavingsConfigResponse {
+        tax_level: config.tax_level,
+        unbonding_period: config.unbonding_period,
+    })
+}
@@ -0,0 +1,236 @@
+use cosmwasm_std::{
+    Deps, DepsMut, Env, Event, MessageInfo, Order, Response, StdResult, Uint128,
+};
+use cw_storage_plus::Bound;
+use cw_utils::Scheduled;
+
+use crate::contract::{change_period, from_base_amount, prepare_balance_hooks, to_base_amount};
+use crate::enumerable::page_limit;
+use crate::error::ContractError;
+use crate::freeze::assert_can_transfer;
+use crate::hook::BalanceDiff;
+use crate::membership::assert_members;
+use crate::query::{ScheduledTransferInfo, ScheduledTransfersResponse};
+use crate::ratelimit::consume_outflow;
+use crate::state::{
+    balances, scheduled_transfers, ScheduledTransfer, FROZEN, SCHEDULED_COUNT, STATE,
+};
+use crate::stats::{record_balance_changes, record_transfer, settle_tiered_tax};
+use crate::vesting::debit_balance;
+
+/// due transfers looked at in one call, bounds the gas spent passing over frozen recipients
+const MAX_SCAN: usize = 300;
+
+/// Escrows amount from the sender until `at` triggers. The escrow is kept in balance
+/// units, so the tokens decay like they would in the recipient's balance.
+pub fn execute_schedule_transfer(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128,
+    at: Scheduled,
+) -> Result<Response, ContractError> {
+    if amount == Uint128::zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    if at.is_triggered(&env.block) {
+        return Err(ContractError::ScheduleInPast {});
+    }
+    let rcpt_addr = deps.api.addr_validate(&recipient)?;
+    assert_can_transfer(deps.storage, &[&info.sender, &rcpt_addr])?;
+    assert_members(deps.as_ref(), &[&info.sender, &rcpt_addr])?;
+
+    let mut state = STATE.load(deps.storage)?;
+    let period_res = change_period(&mut deps, env.clone(), &mut state)?;
+
+    let settled = settle_tiered_tax(deps.storage, &env.block, &[&info.sender])?;
+    let balance = debit_balance(deps.storage, &env.block, &info.sender, amount)?;
+    consume_outflow(deps.storage, &env.block, &info.sender, amount)?;
+
+    let id = SCHEDULED_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    SCHEDULED_COUNT.save(deps.storage, &id)?;
+    let transfer = ScheduledTransfer {
+        sender: info.sender.clone(),
+        recipient: rcpt_addr,
+        amount: Uint128::from(to_base_amount(amount.u128(), state.demurrage_amount)),
+        at,
+    };
+    scheduled_transfers().save(deps.storage, id, &transfer)?;
+
+    let diffs = vec![BalanceDiff::debited(&info.sender, balance, amount)];
+    record_balance_changes(deps.storage, &env.block, &diffs)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
+
+    let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_attribute("action", "schedule_transfer")
+        .add_attribute("id", id.to_string())
+        .add_attribute("from", info.sender)
+        .add_attribute("to", recipient)
+        .add_attribute("amount", amount)
+        .add_attribute("at", transfer.at.to_string());
+    Ok(res)
+}
+
+/// Releases up to limit transfers whose schedule triggered, those due by height first.
+/// Transfers to frozen accounts stay in escrow until they are unfrozen or cancelled and
+/// are passed over, at most MAX_SCAN due transfers are looked at in one call.
+pub fn execute_scheduled(
+    mut deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    assert_can_transfer(deps.storage, &[])?;
+    let limit = page_limit(limit);
+
+    let mut state = STATE.load(deps.storage)?;
+    let period_res = change_period(&mut deps, env.clone(), &mut state)?;
+
+    let release = scheduled_transfers().idx.release;
+    let by_height = release.sub_prefix(0).range(
+        deps.storage,
+        None,
+        Some(Bound::inclusive((env.block.height, u64::MAX))),
+        Order::Ascending,
+    );
+    let by_time = release.sub_prefix(1).range(
+        deps.storage,
+        None,
+        Some(Bound::inclusive((env.block.time.nanos(), u64::MAX))),
+        Order::Ascending,
+    );
+    let mut due: Vec<(u64, ScheduledTransfer)> = vec![];
+    for item in by_height.chain(by_time).take(MAX_SCAN) {
+        let (id, transfer) = item?;
+        if FROZEN.has(deps.storage, &transfer.recipient) {
+            continue;
+        }
+        due.push((id, transfer));
+        if due.len() >= limit {
+            break;
+        }
+    }
+
+    let recipients: Vec<_> = due.iter().map(|(_, transfer)| &transfer.recipient).collect();
+    let settled = settle_tiered_tax(deps.storage, &env.block, &recipients)?;
+    let mut diffs = vec![];
+    let mut events = vec![];
+    for (id, transfer) in &due {
+        scheduled_transfers().remove(deps.storage, *id)?;
+        let balance = balances().update(
+            deps.storage,
+            &transfer.recipient,
+            |balance: Option<Uint128>| -> StdResult<_> {
+                Ok(balance.unwrap_or_default() + transfer.amount)
+            },
+        )?;
+        let value = from_base_amount(transfer.amount, state.demurrage_amount);
+        record_transfer(
+            deps.storage,
+            state.current_period,
+            &transfer.sender,
+            &transfer.recipient,
+            value,
+        )?;
+        diffs.push(BalanceDiff::credited(&transfer.recipient, balance, transfer.amount));
+        events.push(
+            Event::new("scheduled_transfer")
+                .add_attribute("id", id.to_string())
+                .add_attribute("from", &transfer.sender)
+                .add_attribute("to", &transfer.recipient)
+                .add_attribute("amount", value),
+        );
+    }
+    record_balance_changes(deps.storage, &env.block, &diffs)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
+
+    let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_events(events)
+        .add_attribute("action", "execute_scheduled")
+        .add_attribute("released", due.len().to_string());
+    Ok(res)
+}
+
+pub fn execute_cancel_scheduled(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let transfer = scheduled_transfers()
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::ScheduledNotFound { id })?;
+    if transfer.sender != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    assert_can_transfer(deps.storage, &[&info.sender])?;
+
+    let mut state = STATE.load(deps.storage)?;
+    let period_res = change_period(&mut deps, env.clone(), &mut state)?;
+
+    scheduled_transfers().remove(deps.storage, id)?;
+    let settled = settle_tiered_tax(deps.storage, &env.block, &[&info.sender])?;
+    let balance = balances().update(
+        deps.storage,
+        &info.sender,
+        |balance: Option<Uint128>| -> StdResult<_> {
+            Ok(balance.unwrap_or_default() + transfer.amount)
+        },
+    )?;
+    let diffs = vec![BalanceDiff::credited(&info.sender, balance, transfer.amount)];
+    record_balance_changes(deps.storage, &env.block, &diffs)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
+
+    let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_attribute("action", "cancel_scheduled")
+        .add_attribute("id", id.to_string())
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", from_base_amount(transfer.amount, state.demurrage_amount));
+    Ok(res)
+}
+
+pub fn query_scheduled(
+    deps: Deps,
+    sender: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ScheduledTransfersResponse> {
+    let limit = page_limit(limit);
+    let start = start_after.map(Bound::exclusive);
+    let demurrage_amount = STATE.load(deps.storage)?.demurrage_amount;
+
+    let transfers = match sender {
+        Some(sender) => {
+            let sender = deps.api.addr_validate(&sender)?;
+            scheduled_transfers()
+                .idx
+                .sender
+                .prefix(sender)
+                .range(deps.storage, start, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?
+        }
+        None => scheduled_transfers()
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+
+    let transfers = transfers
+        .into_iter()
+        .map(|(id, transfer)| ScheduledTransferInfo {
+            id,
+            sender: transfer.sender.into(),
+            recipient: transfer.recipient.into(),
+            amount: from_base_amount(transfer.amount, demurrage_amount),
+            at: transfer.at,
+        })
+        .collect();
+    Ok(ScheduledTransfersResponse { transfers })
+}
@@ -0,0 +1,225 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+use std::ops::Bound;
+
+use cosmwasm_std::{
+    Addr, Deps, DepsMut, Env, MessageInfo, Order, Record, StdResult, Storage, Uint128,
+};
+
+use crate::contract::{change_period, execute};
+use crate::msg::ExecuteMsg;
+use crate::query::{AccountBalance, SimulateResponse};
+use crate::state::{balances, BALANCES_KEY, STATE};
+use crate::tiers::account_worth;
+
+/// A written value, None marks a removed key
+type Change<'a> = (&'a Vec<u8>, &'a Option<Vec<u8>>);
+
+/// Keeps all writes in memory on top of read-only storage, like the transactions
+/// of multi-test. Simulations drop them, `transactional` writes them back
+pub struct SimulationStorage<'a> {
+    base: &'a dyn Storage,
+    /// None marks a removed key
+    changes: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl<'a> SimulationStorage<'a> {
+    pub fn new(base: &'a dyn Storage) -> Self {
+        SimulationStorage {
+            base,
+            changes: BTreeMap::new(),
+        }
+    }
+
+    /// Keys written or removed so far
+    pub fn changed_keys(&self) -> impl Iterator<Item = &Vec<u8>> {
+        self.changes.keys()
+    }
+}
+
+impl<'a> Storage for SimulationStorage<'a> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.changes.get(key) {
+            Some(value) => value.clone(),
+            None => self.base.get(key),
+        }
+    }
+
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'b> {
+        // BTreeMap::range panics on inverted bounds, they are just an empty range
+        if let (Some(start), Some(end)) = (start, end) {
+            if start > end {
+                return Box::new(std::iter::empty());
+            }
+        }
+        let bounds = (
+            start.map_or(Bound::Unbounded, |s| Bound::Included(s.to_vec())),
+            end.map_or(Bound::Unbounded, |e| Bound::Excluded(e.to_vec())),
+        );
+        let changes = self.changes.range(bounds);
+        let changes: Box<dyn Iterator<Item = Change>> = match order {
+            Order::Ascending => Box::new(changes),
+            Order::Descending => Box::new(changes.rev()),
+        };
+        Box::new(MergedRange {
+            changes: changes.peekable(),
+            base: self.base.range(start, end, order).peekable(),
+            order,
+        })
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.changes.insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.changes.insert(key.to_vec(), None);
+    }
+}
+
+/// Runs action against a SimulationStorage and only writes its changes to storage
+/// when it succeeds, so a failing action leaves no partial updates behind
+pub fn transactional<T, E>(
+    storage: &mut dyn Storage,
+    action: impl FnOnce(&mut dyn Storage) -> Result<T, E>,
+) -> Result<T, E> {
+    let mut overlay = SimulationStorage::new(storage);
+    let res = action(&mut overlay)?;
+    let changes = overlay.changes;
+    for (key, value) in changes {
+        match value {
+            Some(value) => storage.set(&key, &value),
+            None => storage.remove(&key),
+        }
+    }
+    Ok(res)
+}
+
+/// Both ranges in the same order, the changes replace base records with the same key
+struct MergedRange<'a, C, B>
+where
+    C: Iterator<Item = Change<'a>>,
+    B: Iterator<Item = Record>,
+{
+    changes: Peekable<C>,
+    base: Peekable<B>,
+    order: Order,
+}
+
+impl<'a, C, B> Iterator for MergedRange<'a, C, B>
+where
+    C: Iterator<Item = Change<'a>>,
+    B: Iterator<Item = Record>,
+{
+    type Item = Record;
+
+    fn next(&mut self) -> Option<Record> {
+        loop {
+            let take_change = match (self.changes.peek(), self.base.peek()) {
+                (None, None) => return None,
+                (None, Some(_)) => return self.base.next(),
+                (Some(_), None) => true,
+                (Some((change_key, _)), Some((base_key, _))) => {
+                    let cmp = match self.order {
+                        Order::Ascending => change_key.as_slice().cmp(base_key.as_slice()),
+                        Order::Descending => base_key.as_slice().cmp(change_key.as_slice()),
+                    };
+                    match cmp {
+                        Ordering::Less => true,
+                        Ordering::Equal => {
+                            self.base.next();
+                            true
+                        }
+                        Ordering::Greater => return self.base.next(),
+                    }
+                }
+            };
+            if take_change {
+                let (key, value) = self.changes.next()?;
+                // removed keys are skipped
+                if let Some(value) = value {
+                    return Some((key.clone(), value.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// Runs the message against a SimulationStorage and reports its effects.
+/// Funds can not be simulated, the message is sent without any.
+pub fn query_simulate(
+    deps: Deps,
+    env: Env,
+    sender: String,
+    msg: ExecuteMsg,
+) -> StdResult<SimulateResponse> {
+    let sender = deps.api.addr_validate(&sender)?;
+    let mut storage = SimulationStorage::new(deps.storage);
+    let mut simulated = DepsMut {
+        storage: &mut storage,
+        api: deps.api,
+        querier: deps.querier,
+    };
+
+    // closing the periods ahead of the message tells what the sink is credited apart
+    // from what the message moves, the message then finds them closed
+    let mut state = STATE.load(simulated.storage)?;
+    let sink = deps.api.addr_validate(&state.sink_address)?;
+    let sink_before = balances().may_load(simulated.storage, &sink)?.unwrap_or_default();
+    if let Err(err) = change_period(&mut simulated, env.clone(), &mut state) {
+        return Ok(SimulateResponse {
+            error: Some(err.to_string()),
+            ..SimulateResponse::default()
+        });
+    }
+    let sink_after = balances().may_load(simulated.storage, &sink)?.unwrap_or_default();
+    let redistributed = sink_after - sink_before;
+
+    let info = MessageInfo {
+        sender,
+        funds: vec![],
+    };
+    if let Err(err) = execute(simulated, env.clone(), info, msg) {
+        return Ok(SimulateResponse {
+            error: Some(err.to_string()),
+            ..SimulateResponse::default()
+        });
+    }
+    let state = STATE.load(&storage)?;
+
+    // balance keys are the namespace followed by the address
+    let mut prefix = vec![0u8, BALANCES_KEY.len() as u8];
+    prefix.extend_from_slice(BALANCES_KEY.as_bytes());
+    let accounts: Vec<Addr> = storage
+        .changed_keys()
+        .filter_map(|key| key.strip_prefix(prefix.as_slice()))
+        .map(|addr| Addr::unchecked(String::from_utf8_lossy(addr)))
+        .collect();
+    let mut changed = vec![];
+    for address in accounts {
+        let balance = balances().may_load(&storage, &address)?.unwrap_or_default();
+        // written without changing, like a sink receiving nothing
+        if balance == balances().may_load(deps.storage, &address)?.unwrap_or_default() {
+            continue;
+        }
+        let balance = account_worth(&storage, &state, &address, balance, env.block.time)?;
+        changed.push(AccountBalance {
+            address: address.to_string(),
+            balance,
+        });
+    }
+
+    Ok(SimulateResponse {
+        error: None,
+        balances: changed,
+        demurrage_amount: Uint128::from(state.demurrage_amount),
+        current_period: state.current_period,
+        redistributed,
+    })
+}
@@ -1,8 +1,11 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128, Timestamp};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Decimal, Empty, Uint128, Timestamp};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_controllers::{Admin, Claims, Hooks};
+use cw4::Cw4Contract;
+use cw_utils::{Duration, Expiration, Scheduled};
 
 use cw20::{AllowanceResponse};
 
@@ -29,6 +32,22 @@ impl TokenInfo {
     }
 }
 
+/// Additional minter registered by the admin, next to the one in TokenInfo
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MinterConfig {
+    /// cap is how many tokens this minter can issue over its lifetime
+    pub cap: Option<Uint128>,
+    /// total amount issued by this minter so far
+    pub minted: Uint128,
+    /// how many tokens this minter can issue within one demurrage period
+    pub per_period_limit: Option<Uint128>,
+    /// period `period_minted` refers to, the counter resets once a new period starts
+    pub period: u64,
+    pub period_minted: Uint128,
+}
+
+pub const MINTERS: Map<&Addr, MinterConfig> = Map::new("minters");
+
 
 //demurrage state
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -55,8 +74,380 @@ impl State{
 
 
 pub const TOKEN_INFO: Item<TokenInfo> = Item::new("token_info");
-pub const BALANCES: Map<&Addr, Uint128> = Map::new("balance");
+pub struct BalanceIndexes<'a> {
+    /// stored balance amount, to list accounts ordered by their holdings. It is filled
+    /// as balances are saved from instantiation on; the contract has no migrate entry
+    /// point, so there is no earlier state to backfill.
+    pub amount: MultiIndex<'a, u128, Uint128, Addr>,
+}
+
+impl<'a> IndexList<Uint128> for BalanceIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Uint128>> + '_> {
+        let v: Vec<&dyn Index<Uint128>> = vec![&self.amount];
+        Box::new(v.into_iter())
+    }
+}
+
+/// namespace of the balances, its keys are the account address
+pub const BALANCES_KEY: &str = "balance";
+
+pub fn balances<'a>() -> IndexedMap<'a, &'a Addr, Uint128, BalanceIndexes<'a>> {
+    let indexes = BalanceIndexes {
+        amount: MultiIndex::new(|balance: &Uint128| balance.u128(), "balance", "balance__amount"),
+    };
+    IndexedMap::new(BALANCES_KEY, indexes)
+}
 pub const ALLOWANCES: Map<(&Addr, &Addr), AllowanceResponse> = Map::new("allowance");
 
+// ownership
+pub const ADMIN: Admin = Admin::new("admin");
+/// admin and minter proposed by the current one, waiting for their acceptance
+pub const PENDING_ADMIN: Item<PendingOwner> = Item::new("pending_admin");
+pub const PENDING_MINTER: Item<PendingOwner> = Item::new("pending_minter");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PendingOwner {
+    pub new_owner: Addr,
+    /// the proposal can no longer be accepted after this
+    pub expires: Expiration,
+}
+
+// freezing and pausing
+/// may freeze accounts and pause the contract next to the admin
+pub const GUARDIAN: Admin = Admin::new("guardian");
+pub const PAUSED: Item<bool> = Item::new("paused");
+pub const FROZEN: Map<&Addr, Empty> = Map::new("frozen");
+
+/// when set only members of this cw4 group may send or receive tokens
+pub const MEMBERSHIP_GROUP: Item<Cw4Contract> = Item::new("membership_group");
+/// contracts notified with a BalanceChangedHookMsg on every balance change
+pub const HOOKS: Hooks = Hooks::new("balance_hooks");
+
+// native reserve
+/// Native coin backing the token
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ReserveConfig {
+    pub denom: String,
+    /// tokens minted for one unit of the native coin
+    pub ratio: Decimal,
+    /// share of the redeemed tokens sent to the sink instead of being burned
+    pub redemption_fee: Decimal,
+}
+
+pub const RESERVE_CONFIG: Item<ReserveConfig> = Item::new("reserve_config");
+/// amount of native coins held as reserve
+pub const RESERVE: Item<Uint128> = Item::new("reserve");
+
+// vesting
+/// Tokens of an account released linearly from start to end, nothing before the cliff.
+/// The amount is kept in the same units as the stored balance, so locked tokens
+/// are subject to demurrage exactly like the spendable part of the balance.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Vesting {
+    pub amount: Uint128,
+    pub start: Scheduled,
+    pub cliff: Scheduled,
+    pub end: Scheduled,
+}
+
+pub const VESTING: Map<&Addr, Vec<Vesting>> = Map::new("vesting");
+
+// permits
+/// next nonce expected in a permit of the owner
+pub const NONCES: Map<&Addr, u64> = Map::new("permit_nonce");
+
+// progressive demurrage
+/// Demurrage rate for the part of a balance up to `up_to`, above the previous tier
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TaxTier {
+    /// None for the last tier, which covers everything above
+    pub up_to: Option<Uint128>,
+    /// tax per minute, in the same units as the demurrage tax level
+    pub tax_level: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TaxTiers {
+    pub tiers: Vec<TaxTier>,
+    /// accounts last updated before this keep the flat rate until their next change
+    pub since: Timestamp,
+}
+
+/// progressive demurrage, replaces the flat tax level for accounts when set
+pub const TAX_TIERS: Item<TaxTiers> = Item::new("tax_tiers");
+
+/// Part of every balance that is never taxed
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ExemptFloor {
+    pub amount: Uint128,
+    /// accounts last updated before this keep their previous rate until their next change
+    pub since: Timestamp,
+}
+
+pub const EXEMPT_FLOOR: Item<ExemptFloor> = Item::new("exempt_floor");
+
+// account classes
+/// Tax level of an account class, replaces the flat tax level and the tiers for its members
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ClassConfig {
+    pub tax_level: u128,
+    /// members last updated before this keep their previous rate until their next change
+    pub since: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AccountClass {
+    pub class: String,
+    /// members last updated before this keep their previous rate until their next change
+    pub since: Timestamp,
+}
+
+pub struct AccountClassIndexes<'a> {
+    pub class: MultiIndex<'a, String, AccountClass, Addr>,
+}
+
+impl<'a> IndexList<AccountClass> for AccountClassIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<AccountClass>> + '_> {
+        let v: Vec<&dyn Index<AccountClass>> = vec![&self.class];
+        Box::new(v.into_iter())
+    }
+}
+
+/// class assigned to an account by the admin
+pub fn account_classes<'a>() -> IndexedMap<'a, &'a Addr, AccountClass, AccountClassIndexes<'a>> {
+    let indexes = AccountClassIndexes {
+        class: MultiIndex::new(
+            |assigned: &AccountClass| assigned.class.clone(),
+            "account_class",
+            "account_class__class",
+        ),
+    };
+    IndexedMap::new("account_class", indexes)
+}
+
+pub const CLASSES: Map<&str, ClassConfig> = Map::new("classes");
+
+// savings
+/// Terms of the savings locks, set by the admin
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SavingsConfig {
+    /// tax per minute on locked funds, in the same units as the demurrage tax level
+    pub tax_level: u128,
+    /// time between unlocking and being able to claim the tokens
+    pub unbonding_period: Duration,
+}
+
+/// Tokens taken out of the balance for a fixed term. The amount is in base units and
+/// decays with the demurrage modifier like a balance, the value is their worth when
+/// locked, which only the reduced savings tax applies to.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SavingsLock {
+    pub amount: Uint128,
+    pub value: Uint128,
+    pub since: Timestamp,
+    pub locked_until: Expiration,
+}
+
+pub const SAVINGS_CONFIG: Item<SavingsConfig> = Item::new("savings_config");
+pub const SAVINGS_LOCKS: Map<&Addr, Vec<SavingsLock>> = Map::new("savings_locks");
+/// unlocked savings waiting for the unbonding period, in balance units
+pub const SAVINGS_CLAIMS: Claims = Claims::new("savings_claims");
+
+// statistics
+/// Lifetime statistics of an account, amounts are worth at the time of the change
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct AccountStats {
+    pub tax_paid: Uint128,
+    pub sent: Uint128,
+    pub received: Uint128,
+    /// demurrage modifier tax_paid is accounted up to
+    pub demurrage_amount: u128,
+    /// time of the last balance change
+    pub updated: Timestamp,
+}
+
+pub const ACCOUNT_STATS: Map<&Addr, AccountStats> = Map::new("account_stats");
+
+/// Circulation within one demurrage period, amounts as sent
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct PeriodMetrics {
+    pub transfers: u64,
+    pub volume: Uint128,
+    pub unique_senders: u64,
+    pub unique_receivers: u64,
+}
+
+pub const PERIOD_METRICS: Map<u64, PeriodMetrics> = Map::new("period_metrics");
+/// accounts that sent or received within a period, to count each of them once
+pub const PERIOD_SENDERS: Map<(u64, &Addr), Empty> = Map::new("period_senders");
+pub const PERIOD_RECEIVERS: Map<(u64, &Addr), Empty> = Map::new("period_receivers");
+/// last time the account spent tokens
+pub const LAST_ACTIVITY: Map<&Addr, Timestamp> = Map::new("last_activity");
+
+// time-weighted balances
+/// Time-weighted balance of an account at the time of a balance change
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TwabPoint {
+    /// balance-seconds from the start up to this one
+    pub cumulative: Uint128,
+    /// worth of the balance after the change, decaying with the modifier until the next point
+    pub balance: Uint128,
+    /// demurrage modifier the worth was taken at
+    pub demurrage_amount: u128,
+}
+
+/// keyed by account and time of the change in seconds
+pub const TWAB: Map<(&Addr, u64), TwabPoint> = Map::new("twab");
+
+// rate limits
+/// Amount an account, or all accounts together, sent within a period
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AccountOutflow {
+    /// period `used` refers to, the counter resets once a new period starts
+    pub period: u64,
+    pub used: Uint128,
+}
+
+/// how much an account may send within one period
+pub const RATE_LIMITS: Map<&Addr, Uint128> = Map::new("rate_limits");
+pub const OUTFLOW: Map<&Addr, AccountOutflow> = Map::new("outflow");
+/// how much all accounts together may send within one period
+pub const GLOBAL_RATE_LIMIT: Item<Uint128> = Item::new("global_rate_limit");
+/// sent by all accounts in the current period
+pub const GLOBAL_OUTFLOW: Item<AccountOutflow> = Item::new("global_outflow");
+
+// spending rebates
+/// amount an account sent within a period, removed once its rebate is claimed
+pub const SPENDING: Map<(&Addr, u64), Uint128> = Map::new("period_spending");
+/// share of the collected demurrage returned to spenders instead of going to the sink
+pub const REBATE_SHARE: Item<Decimal> = Item::new("rebate_share");
+/// demurrage kept for the spenders of a closed period, in worth at the time it closed
+pub const REBATE_POOLS: Map<u64, Uint128> = Map::new("rebate_pools");
+/// part of the pool of a closed period claimed so far
+pub const REBATE_CLAIMED: Map<u64, Uint128> = Map::new("rebate_claimed");
+
+// universal basic income
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct UbiRound {
+    pub pool: Uint128,
+    /// what is left of the pool, goes to the sink when the round ends
+    pub remaining: Uint128,
+    /// total weight of the group when the round started
+    pub total_weight: u64,
+    /// last member paid in this round
+    pub cursor: Option<String>,
+}
+
+/// when set the collected demurrage is paid out to the members of this cw4 group
+pub const UBI_GROUP: Item<Cw4Contract> = Item::new("ubi_group");
+/// demurrage collected since the last distribution round started
+pub const UBI_PENDING: Item<Uint128> = Item::new("ubi_pending");
+/// distribution round in progress, removed once all members are paid
+pub const UBI_ROUND: Item<UbiRound> = Item::new("ubi_round");
+
+// scheduled transfers
+/// Transfer escrowed by the contract until its schedule triggers
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ScheduledTransfer {
+    pub sender: Addr,
+    pub recipient: Addr,
+    /// escrowed in balance units, so it keeps decaying like any balance until released
+    pub amount: Uint128,
+    pub at: Scheduled,
+}
+
+pub struct ScheduledTransferIndexes<'a> {
+    pub sender: MultiIndex<'a, Addr, ScheduledTransfer, u64>,
+    /// release height under 0 and release time (in nanos) under 1, to range over due transfers
+    pub release: MultiIndex<'a, (u8, u64), ScheduledTransfer, u64>,
+}
+
+impl<'a> IndexList<ScheduledTransfer> for ScheduledTransferIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<ScheduledTransfer>> + '_> {
+        let v: Vec<&dyn Index<ScheduledTransfer>> = vec![&self.sender, &self.release];
+        Box::new(v.into_iter())
+    }
+}
+
+/// pending scheduled transfers by id, removed once released or cancelled
+pub fn scheduled_transfers<'a>() -> IndexedMap<'a, u64, ScheduledTransfer, ScheduledTransferIndexes<'a>> {
+    let indexes = ScheduledTransferIndexes {
+        sender: MultiIndex::new(
+            |transfer: &ScheduledTransfer| transfer.sender.clone(),
+            "scheduled",
+            "scheduled__sender",
+        ),
+        release: MultiIndex::new(
+            |transfer: &ScheduledTransfer| match transfer.at {
+                Scheduled::AtHeight(height) => (0, height),
+                Scheduled::AtTime(time) => (1, time.nanos()),
+            },
+            "scheduled",
+            "scheduled__release",
+        ),
+    };
+    IndexedMap::new("scheduled", indexes)
+}
+
+/// id of the last scheduled transfer
+pub const SCHEDULED_COUNT: Item<u64> = Item::new("scheduled_count");
+
+// subscriptions
+/// Recurring payment charged from the allowance the payer gave the payee
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Subscription {
+    pub payer: Addr,
+    pub payee: Addr,
+    pub amount: Uint128,
+    pub interval: Duration,
+    pub next_payment: Expiration,
+    /// None keeps charging until cancelled
+    pub payments_left: Option<u32>,
+}
+
+pub struct SubscriptionIndexes<'a> {
+    pub payer: MultiIndex<'a, Addr, Subscription, u64>,
+    pub payee: MultiIndex<'a, Addr, Subscription, u64>,
+    /// next payment height under 0 and next payment time (in nanos) under 1
+    pub next_payment: MultiIndex<'a, (u8, u64), Subscription, u64>,
+}
+
+impl<'a> IndexList<Subscription> for SubscriptionIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Subscription>> + '_> {
+        let v: Vec<&dyn Index<Subscription>> = vec![&self.payer, &self.payee, &self.next_payment];
+        Box::new(v.into_iter())
+    }
+}
+
+/// active subscriptions by id, removed after the last payment or when cancelled
+pub fn subscriptions<'a>() -> IndexedMap<'a, u64, Subscription, SubscriptionIndexes<'a>> {
+    let indexes = SubscriptionIndexes {
+        payer: MultiIndex::new(
+            |subscription: &Subscription| subscription.payer.clone(),
+            "subscriptions",
+            "subscriptions__payer",
+        ),
+        payee: MultiIndex::new(
+            |subscription: &Subscription| subscription.payee.clone(),
+            "subscriptions",
+            "subscriptions__payee",
+        ),
+        next_payment: MultiIndex::new(
+            |subscription: &Subscription| match subscription.next_payment {
+                Expiration::AtHeight(height) => (0, height),
+                Expiration::AtTime(time) => (1, time.nanos()),
+                // never created, kept out of the due ranges
+                Expiration::Never {} => (2, 0),
+            },
+            "subscriptions",
+            "subscriptions__next_payment",
+        ),
+    };
+    IndexedMap::new("subscriptions", indexes)
+}
+
+/// id of the last subscription
+pub const SUBSCRIPTION_COUNT: Item<u64> = Item::new("subscription_count");
+
 //demurrage state 
 pub const STATE: Item<State> = Item::new("demurrage_state");
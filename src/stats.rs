@@ -0,0 +1,202 @@
+use cosmwasm_std::{Addr, BlockInfo, Deps, Empty, Env, StdResult, Storage, Uint128};
+
+use crate::contract::{from_base_amount, to_base_amount};
+use crate::hook::BalanceDiff;
+use crate::rebates::record_spending;
+use crate::tiers::{account_tiers, account_worth, tiered_tax};
+use crate::twab::record_twab;
+use crate::query::{AccountStatsResponse, PeriodMetricsResponse};
+use crate::state::{
+    AccountStats, PeriodMetrics, TaxTiers, ACCOUNT_STATS, LAST_ACTIVITY, PERIOD_METRICS, PERIOD_RECEIVERS,
+    PERIOD_SENDERS, STATE, balances,
+};
+
+/// Worth the balance lost to demurrage since the stats were last updated
+fn accrued_tax(stats: &AccountStats, balance: Uint128, demurrage_amount: u128) -> Uint128 {
+    from_base_amount(balance, stats.demurrage_amount)
+        .saturating_sub(from_base_amount(balance, demurrage_amount))
+}
+
+/// Update the statistics of every account in the diffs. The demurrage taken from the old
+/// balance is accounted first, then the change is booked as sent or received at its
+/// current worth. Every balance decrease counts as sent, every increase as received.
+/// The time-weighted balance of the account is updated with the new balance.
+pub fn record_balance_changes(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    diffs: &[BalanceDiff],
+) -> StdResult<()> {
+    if diffs.is_empty() {
+        return Ok(());
+    }
+    let state = STATE.load(storage)?;
+    let demurrage_amount = state.demurrage_amount;
+
+    for diff in diffs {
+        let addr = Addr::unchecked(&diff.address);
+        let mut stats = ACCOUNT_STATS
+            .may_load(storage, &addr)?
+            .unwrap_or(AccountStats {
+                demurrage_amount,
+                updated: block.time,
+                ..AccountStats::default()
+            });
+        let mut tax = accrued_tax(&stats, diff.old, demurrage_amount);
+        // settled accounts were updated in this block, so no tiered tax is left
+        if let Some(tiers) = active_tiers(storage, &addr, &stats)? {
+            let minutes = (block.time.seconds() - stats.updated.seconds()) / 60;
+            tax = tiered_tax(&tiers.tiers, from_base_amount(diff.old, stats.demurrage_amount), minutes);
+        }
+
+        stats.tax_paid += tax;
+        stats.demurrage_amount = demurrage_amount;
+        stats.updated = block.time;
+        if diff.new > diff.old {
+            stats.received += from_base_amount(diff.new - diff.old, demurrage_amount);
+        } else {
+            stats.sent += from_base_amount(diff.old - diff.new, demurrage_amount);
+        }
+        ACCOUNT_STATS.save(storage, &addr, &stats)?;
+        record_twab(storage, block, &state, &addr, diff.old, diff.new)?;
+    }
+    Ok(())
+}
+
+/// Class or tax tiers of addr, once the account was updated after they took effect
+fn active_tiers(storage: &dyn Storage, addr: &Addr, stats: &AccountStats) -> StdResult<Option<TaxTiers>> {
+    let tiers = account_tiers(storage, addr)?.filter(|tiers| stats.updated >= tiers.since);
+    Ok(tiers)
+}
+
+/// Settle the difference between the flat demurrage and the tax of every account with
+/// a class or tax tiers since its last change. The flat demurrage went to the sink, so
+/// the sink pays back what it took beyond the tiered tax and receives what it missed,
+/// never more than the paying side holds. Must run before the balances of the accounts
+/// change. Returns the balance changes, which are already recorded.
+pub fn settle_tiered_tax(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    accounts: &[&Addr],
+) -> StdResult<Vec<BalanceDiff>> {
+    let state = STATE.load(storage)?;
+    let sink = Addr::unchecked(&state.sink_address);
+    let mut diffs = vec![];
+
+    for addr in accounts.iter().copied() {
+        // whatever the sink pays itself stays at the sink
+        if *addr == sink {
+            continue;
+        }
+        let mut stats = match ACCOUNT_STATS.may_load(storage, addr)? {
+            Some(stats) => stats,
+            None => continue,
+        };
+        let tiers = match active_tiers(storage, addr, &stats)? {
+            Some(tiers) => tiers,
+            None => continue,
+        };
+        let balance = balances().may_load(storage, addr)?.unwrap_or_default();
+        let flat = accrued_tax(&stats, balance, state.demurrage_amount);
+        let minutes = (block.time.seconds() - stats.updated.seconds()) / 60;
+        let tiered = tiered_tax(&tiers.tiers, from_base_amount(balance, stats.demurrage_amount), minutes);
+
+        stats.tax_paid += tiered;
+        stats.demurrage_amount = state.demurrage_amount;
+        stats.updated = block.time;
+        ACCOUNT_STATS.save(storage, addr, &stats)?;
+        if flat == tiered {
+            continue;
+        }
+
+        let refund = flat > tiered;
+        let (payer, payee) = if refund { (&sink, addr) } else { (addr, &sink) };
+        let worth = if refund { flat - tiered } else { tiered - flat };
+        let amount = Uint128::from(to_base_amount(worth.u128(), state.demurrage_amount))
+            .min(balances().may_load(storage, payer)?.unwrap_or_default());
+        if amount.is_zero() {
+            continue;
+        }
+        let payer_balance = balances().update(storage, payer, |balance| -> StdResult<_> {
+            Ok(balance.unwrap_or_default() - amount)
+        })?;
+        let payee_balance = balances().update(storage, payee, |balance| -> StdResult<_> {
+            Ok(balance.unwrap_or_default() + amount)
+        })?;
+        let paid = BalanceDiff::debited(payer, payer_balance, amount);
+        let received = BalanceDiff::credited(payee, payee_balance, amount);
+
+        // the account only settles its tax, the sink books it like a redistribution
+        let (account, sink_diff) = if refund { (received, paid) } else { (paid, received) };
+        record_twab(storage, block, &state, addr, account.old, account.new)?;
+        record_balance_changes(storage, block, std::slice::from_ref(&sink_diff))?;
+        diffs.push(account);
+        diffs.push(sink_diff);
+    }
+    Ok(diffs)
+}
+
+pub fn query_account_stats(deps: Deps, env: Env, address: String) -> StdResult<AccountStatsResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let last_spend = LAST_ACTIVITY.may_load(deps.storage, &addr)?;
+    let stats = match ACCOUNT_STATS.may_load(deps.storage, &addr)? {
+        Some(stats) => stats,
+        None => {
+            return Ok(AccountStatsResponse {
+                last_spend,
+                ..AccountStatsResponse::default()
+            })
+        }
+    };
+
+    // include the demurrage accrued on the current balance up to this block
+    let state = STATE.load(deps.storage)?;
+    let balance = balances().may_load(deps.storage, &addr)?.unwrap_or_default();
+    let worth = account_worth(deps.storage, &state, &addr, balance, env.block.time)?;
+    let tax_paid = stats.tax_paid + from_base_amount(balance, stats.demurrage_amount).saturating_sub(worth);
+
+    Ok(AccountStatsResponse {
+        tax_paid,
+        sent: stats.sent,
+        received: stats.received,
+        last_spend,
+    })
+}
+
+/// Count a transfer in the metrics of the period
+pub fn record_transfer(
+    storage: &mut dyn Storage,
+    period: u64,
+    from: &Addr,
+    to: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    record_spending(storage, period, from, amount)?;
+    let mut metrics = PERIOD_METRICS.may_load(storage, period)?.unwrap_or_default();
+    metrics.transfers += 1;
+    metrics.volume += amount;
+    if !PERIOD_SENDERS.has(storage, (period, from)) {
+        PERIOD_SENDERS.save(storage, (period, from), &Empty {})?;
+        metrics.unique_senders += 1;
+    }
+    if !PERIOD_RECEIVERS.has(storage, (period, to)) {
+        PERIOD_RECEIVERS.save(storage, (period, to), &Empty {})?;
+        metrics.unique_receivers += 1;
+    }
+    PERIOD_METRICS.save(storage, period, &metrics)
+}
+
+/// Start the counters of a new period from zero
+pub fn roll_over_metrics(storage: &mut dyn Storage, period: u64) -> StdResult<()> {
+    PERIOD_METRICS.save(storage, period, &PeriodMetrics::default())
+}
+
+pub fn query_period_metrics(deps: Deps, period: u64) -> StdResult<PeriodMetricsResponse> {
+    let metrics = PERIOD_METRICS.may_load(deps.storage, period)?.unwrap_or_default();
+    Ok(PeriodMetricsResponse {
+        period,
+        transfers: metrics.transfers,
+        volume: metrics.volume,
+        unique_senders: metrics.unique_senders,
+        unique_receivers: metrics.unique_receivers,
+    })
+}
@@ -0,0 +1,225 @@
+use cosmwasm_std::{
+    Deps, DepsMut, Env, Event, MessageInfo, Order, Response, StdResult, Uint128,
+};
+use cw_storage_plus::Bound;
+use cw_utils::{Duration, Expiration};
+
+use crate::allowances::deduct_allowance;
+use crate::contract::{change_period, prepare_balance_hooks, transfer_tokens};
+use crate::enumerable::page_limit;
+use crate::error::ContractError;
+use crate::freeze::assert_can_transfer;
+use crate::membership::assert_members;
+use crate::query::{SubscriptionInfo, SubscriptionsResponse};
+use crate::simulate::transactional;
+use crate::state::{subscriptions, Subscription, STATE, SUBSCRIPTION_COUNT};
+use crate::stats::settle_tiered_tax;
+
+/// Registers a recurring payment from the sender to the payee. Nothing is moved here,
+/// the payee needs an allowance from the sender covering the payments.
+pub fn execute_create_subscription(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    payee: String,
+    amount: Uint128,
+    interval: Duration,
+    max_payments: Option<u32>,
+) -> Result<Response, ContractError> {
+    if amount == Uint128::zero() || max_payments == Some(0) {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    let payee_addr = deps.api.addr_validate(&payee)?;
+    if payee_addr == info.sender {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+    // the first payment is due right away
+    let next_payment = match interval {
+        Duration::Height(0) | Duration::Time(0) => {
+            return Err(ContractError::InvalidSubscriptionInterval {})
+        }
+        Duration::Height(_) => Expiration::AtHeight(env.block.height),
+        Duration::Time(_) => Expiration::AtTime(env.block.time),
+    };
+
+    let id = SUBSCRIPTION_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    SUBSCRIPTION_COUNT.save(deps.storage, &id)?;
+    let subscription = Subscription {
+        payer: info.sender.clone(),
+        payee: payee_addr,
+        amount,
+        interval,
+        next_payment,
+        payments_left: max_payments,
+    };
+    subscriptions().save(deps.storage, id, &subscription)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "create_subscription")
+        .add_attribute("id", id.to_string())
+        .add_attribute("payer", info.sender)
+        .add_attribute("payee", payee)
+        .add_attribute("amount", amount))
+}
+
+pub fn execute_cancel_subscription(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let subscription = subscriptions()
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::SubscriptionNotFound { id })?;
+    if subscription.payer != info.sender && subscription.payee != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    subscriptions().remove(deps.storage, id)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_subscription")
+        .add_attribute("id", id.to_string())
+        .add_attribute("by", info.sender))
+}
+
+/// Charges up to limit due subscriptions, those due by height first. Every payment runs
+/// on its own without affecting the others. A payment without an allowance, or with an
+/// expired one, cancels its subscription. Other failures, like a low balance or a frozen
+/// account, skip the payment and the subscription is due again after its interval, so it
+/// does not stay in the way of the ones behind it.
+pub fn execute_process_subscriptions(
+    mut deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    assert_can_transfer(deps.storage, &[])?;
+    let limit = page_limit(limit);
+
+    let mut state = STATE.load(deps.storage)?;
+    let period_res = change_period(&mut deps, env.clone(), &mut state)?;
+
+    let next_payment = subscriptions().idx.next_payment;
+    let by_height = next_payment.sub_prefix(0).range(
+        deps.storage,
+        None,
+        Some(Bound::inclusive((env.block.height, u64::MAX))),
+        Order::Ascending,
+    );
+    let by_time = next_payment.sub_prefix(1).range(
+        deps.storage,
+        None,
+        Some(Bound::inclusive((env.block.time.nanos(), u64::MAX))),
+        Order::Ascending,
+    );
+    let due = by_height
+        .chain(by_time)
+        .take(limit)
+        .collect::<StdResult<Vec<(u64, Subscription)>>>()?;
+
+    let mut diffs = vec![];
+    let mut events = vec![];
+    let mut charged = 0u32;
+    for (id, mut subscription) in due {
+        let payment = assert_members(deps.as_ref(), &[&subscription.payer, &subscription.payee])
+            .and_then(|_| {
+                transactional(deps.storage, |storage| {
+                    assert_can_transfer(storage, &[&subscription.payer, &subscription.payee])?;
+                    deduct_allowance(
+                        storage,
+                        &subscription.payer,
+                        &subscription.payee,
+                        &env.block,
+                        subscription.amount,
+                    )?;
+                    let accounts = [&subscription.payer, &subscription.payee];
+                    let mut diffs = settle_tiered_tax(storage, &env.block, &accounts)?;
+                    diffs.extend(transfer_tokens(
+                        storage,
+                        &env.block,
+                        &state,
+                        &subscription.payer,
+                        &subscription.payee,
+                        subscription.amount,
+                    )?);
+                    Ok(diffs)
+                })
+            });
+
+        let event = Event::new("subscription_payment")
+            .add_attribute("id", id.to_string())
+            .add_attribute("payer", &subscription.payer)
+            .add_attribute("payee", &subscription.payee)
+            .add_attribute("amount", subscription.amount);
+        match payment {
+            Ok(payment_diffs) => {
+                charged += 1;
+                diffs.extend(payment_diffs);
+                events.push(event.add_attribute("result", "paid"));
+                subscription.payments_left = subscription.payments_left.map(|left| left - 1);
+            }
+            Err(err) => {
+                events.push(event.add_attribute("result", err.to_string()));
+                if matches!(err, ContractError::NoAllowance {} | ContractError::Expired {}) {
+                    subscription.payments_left = Some(0);
+                }
+            }
+        }
+
+        if subscription.payments_left == Some(0) {
+            subscriptions().remove(deps.storage, id)?;
+        } else {
+            subscription.next_payment = (subscription.next_payment + subscription.interval)?;
+            subscriptions().save(deps.storage, id, &subscription)?;
+        }
+    }
+    let hooks = prepare_balance_hooks(deps.storage, diffs)?;
+
+    let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_events(events)
+        .add_attribute("action", "process_subscriptions")
+        .add_attribute("charged", charged.to_string());
+    Ok(res)
+}
+
+pub fn query_subscriptions_by_payer(
+    deps: Deps,
+    payer: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<SubscriptionsResponse> {
+    let payer = deps.api.addr_validate(&payer)?;
+    let subscriptions = subscriptions()
+        .idx
+        .payer
+        .prefix(payer)
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending);
+    list_subscriptions(subscriptions, limit)
+}
+
+pub fn query_subscriptions_by_payee(
+    deps: Deps,
+    payee: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<SubscriptionsResponse> {
+    let payee = deps.api.addr_validate(&payee)?;
+    let subscriptions = subscriptions()
+        .idx
+        .payee
+        .prefix(payee)
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending);
+    list_subscriptions(subscriptions, limit)
+}
+
+fn list_subscriptions(
+    items: impl Iterator<Item = StdResult<(u64, Subscription)>>,
+    limit: Option<u32>,
+) -> StdResult<SubscriptionsResponse> {
+    let limit = page_limit(limit);
+    let subscriptions = items
+        .take(limit)
+        .map(|item| item.map(|(id, subscription)| SubscriptionInfo { id, subscription }))
+        .collect::<StdResult<_>>()?;
+    Ok(SubscriptionsResponse { subscriptions })
+}
@@ -0,0 +1,158 @@
+use cosmwasm_std::{Addr, DepsMut, Env, Event, Order, Response, StdResult, Storage, Uint128};
+use cw_storage_plus::Bound;
+
+use crate::contract::{change_period, from_base_amount, prepare_balance_hooks};
+use crate::enumerable::page_limit;
+use crate::error::ContractError;
+use crate::freeze::assert_can_transfer;
+use crate::hook::BalanceDiff;
+use crate::state::{
+    balances, ACCOUNT_STATS, FROZEN, LAST_ACTIVITY, OUTFLOW, SPENDING, STATE, TOKEN_INFO, TWAB,
+    VESTING,
+};
+use crate::stats::{record_balance_changes, settle_tiered_tax};
+use crate::vesting::locked_balance;
+
+/// Accounts that did not spend anything for this long are considered dormant (30 days)
+const DORMANT_AFTER_SECONDS: u64 = 30 * 24 * 60 * 60;
+
+/// Removes dormant balances worth less than half a display unit, smallest first,
+/// and moves what is left of them to the sink together with the records of the account.
+/// Anyone may call this.
+/// Frozen accounts and accounts with locked vesting are left untouched.
+///
+/// At most limit accounts are looked at. When the scan stops there, the last account
+/// left in place is returned as start_after: the swept accounts after it are gone, so
+/// the next call continues right behind it. Without it the next call starts over.
+pub fn execute_sweep_dust(
+    mut deps: DepsMut,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    assert_can_transfer(deps.storage, &[])?;
+    let limit = page_limit(limit);
+
+    let mut state = STATE.load(deps.storage)?;
+    let period_res = change_period(&mut deps, env.clone(), &mut state)?;
+
+    let unit = 10u128.pow(TOKEN_INFO.load(deps.storage)?.decimals);
+    let sink_addr = deps.api.addr_validate(&state.sink_address)?;
+
+    // an account that left the balances since starts the scan over
+    let mut last_kept: Option<Addr> = None;
+    let mut start = None;
+    if let Some(addr) = start_after {
+        let addr = deps.api.addr_validate(&addr)?;
+        if let Some(balance) = balances().may_load(deps.storage, &addr)? {
+            start = Some(Bound::exclusive((balance.u128(), addr.clone())));
+            last_kept = Some(addr);
+        }
+    }
+
+    let mut dust: Vec<(Addr, Uint128)> = vec![];
+    let mut exhausted = true;
+    for (visited, item) in balances()
+        .idx
+        .amount
+        .range(deps.storage, start, None, Order::Ascending)
+        .enumerate()
+    {
+        let (addr, balance) = item?;
+        // the index is sorted by amount, so everything after is worth more
+        if from_base_amount(balance, state.demurrage_amount).u128() * 2 >= unit {
+            break;
+        }
+        if visited == limit {
+            exhausted = false;
+            break;
+        }
+        if addr == sink_addr
+            || !is_dormant(deps.storage, &env, &addr)?
+            || FROZEN.has(deps.storage, &addr)
+            || !locked_balance(deps.storage, &env.block, &addr)?.is_zero()
+        {
+            last_kept = Some(addr);
+            continue;
+        }
+        dust.push((addr, balance));
+    }
+
+    let accounts: Vec<_> = dust.iter().map(|(addr, _)| addr).collect();
+    let settled = settle_tiered_tax(deps.storage, &env.block, &accounts)?;
+    let mut diffs = vec![];
+    let mut events = vec![];
+    for (addr, balance) in dust.iter_mut() {
+        let addr = &*addr;
+        // the settlement may have moved the balance
+        *balance = balances().load(deps.storage, addr)?;
+        balances().remove(deps.storage, addr)?;
+        diffs.push(BalanceDiff::new(addr, *balance, Uint128::zero()));
+        events.push(
+            Event::new("dust_swept")
+                .add_attribute("address", addr)
+                .add_attribute("amount", *balance),
+        );
+    }
+
+    let total: Uint128 = dust.iter().map(|(_, balance)| balance).sum();
+    if !total.is_zero() {
+        let sink_balance = balances().update(
+            deps.storage,
+            &sink_addr,
+            |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + total) },
+        )?;
+        diffs.push(BalanceDiff::credited(&sink_addr, sink_balance, total));
+    }
+    record_balance_changes(deps.storage, &env.block, &diffs)?;
+    for (addr, _) in &dust {
+        remove_account_records(deps.storage, addr)?;
+    }
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
+
+    let mut res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_events(events)
+        .add_attribute("action", "sweep_dust")
+        .add_attribute("accounts", dust.len().to_string())
+        .add_attribute("amount", total);
+    if !exhausted {
+        if let Some(addr) = last_kept {
+            res = res.add_attribute("start_after", addr);
+        }
+    }
+    Ok(res)
+}
+
+/// Drops what is kept about the activity of a swept account. Settings made for it, like
+/// a rate limit or a class, and the permit nonce stay, unclaimed rebates are forfeited.
+fn remove_account_records(storage: &mut dyn Storage, addr: &Addr) -> StdResult<()> {
+    ACCOUNT_STATS.remove(storage, addr);
+    LAST_ACTIVITY.remove(storage, addr);
+    OUTFLOW.remove(storage, addr);
+    VESTING.remove(storage, addr);
+    let points = TWAB
+        .prefix(addr)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for time in points {
+        TWAB.remove(storage, (addr, time));
+    }
+    let periods = SPENDING
+        .prefix(addr)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for period in periods {
+        SPENDING.remove(storage, (addr, period));
+    }
+    Ok(())
+}
+
+fn is_dormant(storage: &dyn Storage, env: &Env, addr: &Addr) -> StdResult<bool> {
+    let dormant = match LAST_ACTIVITY.may_load(storage, addr)? {
+        Some(last) => last.plus_seconds(DORMANT_AFTER_SECONDS) <= env.block.time,
+        None => true,
+    };
+    Ok(dormant)
+}
@@ -0,0 +1,194 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Timestamp, Uint128};
+
+use crate::contract::{decay_by, from_base_amount, RESOLUTION_FACTOR};
+use crate::error::ContractError;
+use crate::projection::projected_demurrage_amount;
+use crate::query::{TaxTiersResponse, TaxableBalanceResponse};
+use crate::state::{
+    account_classes, balances, ExemptFloor, State, TaxTier, TaxTiers, ACCOUNT_STATS, ADMIN,
+    CLASSES, EXEMPT_FLOOR, STATE, TAX_TIERS,
+};
+
+/// Tiers must cover all amounts from zero without gaps: every tier but the last ends
+/// above the previous one, the last one is unbounded and rates never decrease
+pub fn validate_tiers(tiers: &[TaxTier]) -> Result<(), ContractError> {
+    // the highest rate is the last one, it must not take more than everything
+    let last = tiers.last().ok_or(ContractError::InvalidTaxTiers {})?;
+    if last.up_to.is_some() || last.tax_level > RESOLUTION_FACTOR {
+        return Err(ContractError::InvalidTaxTiers {});
+    }
+    let mut lower = Uint128::zero();
+    for pair in tiers.windows(2) {
+        let (tier, next) = (&pair[0], &pair[1]);
+        match tier.up_to {
+            Some(up_to) if up_to > lower && tier.tax_level <= next.tax_level => lower = up_to,
+            _ => return Err(ContractError::InvalidTaxTiers {}),
+        }
+    }
+    Ok(())
+}
+
+/// Demurrage the tiers take from worth over the given number of minutes.
+/// The split into tiers is taken at the start of the span.
+pub fn tiered_tax(tiers: &[TaxTier], worth: Uint128, minutes: u64) -> Uint128 {
+    let mut lower = Uint128::zero();
+    let mut tax = Uint128::zero();
+    for tier in tiers {
+        let upper = tier.up_to.map_or(worth, |up_to| up_to.min(worth));
+        if upper <= lower {
+            break;
+        }
+        let slice = (upper - lower).u128();
+        tax += Uint128::from(slice - decay_by(slice, tier.tax_level, minutes));
+        lower = upper;
+    }
+    tax
+}
+
+/// Rates that replace the flat demurrage for addr: a single tier with the rate of its
+/// class if it has one, otherwise the global tiers if any. With an exempt floor, a
+/// tax free tier covering the floor is put in front of them.
+pub fn account_tiers(storage: &dyn Storage, addr: &Addr) -> StdResult<Option<TaxTiers>> {
+    let tiers = match account_classes().may_load(storage, addr)? {
+        Some(assigned) => {
+            let class = CLASSES.load(storage, &assigned.class)?;
+            Some(TaxTiers {
+                tiers: vec![TaxTier {
+                    up_to: None,
+                    tax_level: class.tax_level,
+                }],
+                since: assigned.since.max(class.since),
+            })
+        }
+        None => TAX_TIERS.may_load(storage)?,
+    };
+
+    let floor = match EXEMPT_FLOOR.may_load(storage)? {
+        Some(floor) => floor,
+        None => return Ok(tiers),
+    };
+    let TaxTiers { tiers, since } = match tiers {
+        Some(tiers) => tiers,
+        None => TaxTiers {
+            tiers: vec![TaxTier {
+                up_to: None,
+                tax_level: STATE.load(storage)?.tax_level,
+            }],
+            since: floor.since,
+        },
+    };
+    let mut shifted = vec![TaxTier {
+        up_to: Some(floor.amount),
+        tax_level: 0,
+    }];
+    shifted.extend(tiers.into_iter().map(|tier| TaxTier {
+        up_to: tier.up_to.map(|up_to| up_to + floor.amount),
+        tax_level: tier.tax_level,
+    }));
+    Ok(Some(TaxTiers {
+        tiers: shifted,
+        since: since.max(floor.since),
+    }))
+}
+
+/// Worth of the stored balance of addr at the given time. Once the account was
+/// updated under its current class or tiers, its worth decays by those instead of the
+/// flat demurrage modifier.
+pub fn account_worth(
+    storage: &dyn Storage,
+    state: &State,
+    addr: &Addr,
+    balance: Uint128,
+    time: Timestamp,
+) -> StdResult<Uint128> {
+    let tiers = account_tiers(storage, addr)?;
+    let stats = ACCOUNT_STATS.may_load(storage, addr)?;
+    let worth = match (tiers, stats) {
+        (Some(tiers), Some(stats)) if stats.updated >= tiers.since => {
+            let worth = from_base_amount(balance, stats.demurrage_amount);
+            let minutes = (time.seconds().saturating_sub(stats.updated.seconds())) / 60;
+            worth - tiered_tax(&tiers.tiers, worth, minutes)
+        }
+        _ => from_base_amount(balance, projected_demurrage_amount(state, time)),
+    };
+    Ok(worth)
+}
+
+/// Replace the tax tiers, an empty list returns to the flat tax level.
+/// Accounts switch to the new tiers with their next balance change.
+pub fn execute_update_tax_tiers(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tiers: Vec<TaxTier>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    if tiers.is_empty() {
+        TAX_TIERS.remove(deps.storage);
+    } else {
+        validate_tiers(&tiers)?;
+        TAX_TIERS.save(
+            deps.storage,
+            &TaxTiers {
+                tiers: tiers.clone(),
+                since: env.block.time,
+            },
+        )?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_tax_tiers")
+        .add_attribute("tiers", tiers.len().to_string()))
+}
+
+pub fn query_tax_tiers(deps: Deps) -> StdResult<TaxTiersResponse> {
+    let tiers = TAX_TIERS
+        .may_load(deps.storage)?
+        .map(|config| config.tiers)
+        .unwrap_or_default();
+    Ok(TaxTiersResponse { tiers })
+}
+
+/// Set the part of every balance that is never taxed, zero removes the floor.
+/// Accounts switch to the new floor with their next balance change.
+pub fn execute_set_exempt_floor(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    if amount.is_zero() {
+        EXEMPT_FLOOR.remove(deps.storage);
+    } else {
+        let floor = ExemptFloor {
+            amount,
+            since: env.block.time,
+        };
+        EXEMPT_FLOOR.save(deps.storage, &floor)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_exempt_floor")
+        .add_attribute("amount", amount))
+}
+
+pub fn query_taxable_balance(deps: Deps, env: Env, address: String) -> StdResult<TaxableBalanceResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let state = STATE.load(deps.storage)?;
+    let balance = balances().may_load(deps.storage, &addr)?.unwrap_or_default();
+    let balance = account_worth(deps.storage, &state, &addr, balance, env.block.time)?;
+
+    let floor = EXEMPT_FLOOR
+        .may_load(deps.storage)?
+        .map(|floor| floor.amount)
+        .unwrap_or_default();
+    let exempt = balance.min(floor);
+    Ok(TaxableBalanceResponse {
+        balance,
+        exempt,
+        taxable: balance - exempt,
+    })
+}
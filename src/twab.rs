@@ -0,0 +1,129 @@
+use cosmwasm_std::{Addr, BlockInfo, Deps, Env, Order, StdError, StdResult, Storage, Timestamp, Uint128};
+use cw_storage_plus::Bound;
+
+use crate::contract::from_base_amount;
+use crate::projection::projected_demurrage_amount;
+use crate::query::TwabBalanceResponse;
+use crate::state::{balances, State, TwabPoint, STATE, TWAB};
+
+fn latest_point(storage: &dyn Storage, addr: &Addr, until: Option<u64>) -> StdResult<Option<(u64, TwabPoint)>> {
+    TWAB.prefix(addr)
+        .range(storage, None, until.map(Bound::inclusive), Order::Descending)
+        .next()
+        .transpose()
+}
+
+fn first_point(storage: &dyn Storage, addr: &Addr) -> StdResult<Option<(u64, TwabPoint)>> {
+    TWAB.prefix(addr)
+        .range(storage, None, None, Order::Ascending)
+        .next()
+        .transpose()
+}
+
+fn next_point(storage: &dyn Storage, addr: &Addr, after: u64) -> StdResult<Option<(u64, TwabPoint)>> {
+    TWAB.prefix(addr)
+        .range(storage, Some(Bound::exclusive(after)), None, Order::Ascending)
+        .next()
+        .transpose()
+}
+
+/// Balance-seconds of the worth of a point held for secs, while the modifier moves from
+/// the one of the point to demurrage_amount. The worth decays linearly in between.
+fn held(point: &TwabPoint, demurrage_amount: u128, secs: u64) -> Uint128 {
+    let held = point.balance * Uint128::from(secs);
+    (held + held.multiply_ratio(demurrage_amount, point.demurrage_amount)) / Uint128::new(2)
+}
+
+/// Add a point with the worth of the new balance of addr, closing the span since the
+/// previous point with the demurrage applied in between. Balances that predate the first
+/// point are held since the start.
+pub fn record_twab(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    state: &State,
+    addr: &Addr,
+    old: Uint128,
+    new: Uint128,
+) -> StdResult<()> {
+    let now = block.time.seconds();
+    let (time, point) = match latest_point(storage, addr, None)? {
+        Some(latest) => latest,
+        None => (
+            state.start_timestamp.seconds(),
+            TwabPoint {
+                cumulative: Uint128::zero(),
+                balance: from_base_amount(old, state.demurrage_amount),
+                demurrage_amount: state.demurrage_amount,
+            },
+        ),
+    };
+    let point = TwabPoint {
+        cumulative: point.cumulative + held(&point, state.demurrage_amount, now - time),
+        balance: from_base_amount(new, state.demurrage_amount),
+        demurrage_amount: state.demurrage_amount,
+    };
+    TWAB.save(storage, (addr, now), &point)
+}
+
+/// Balance-seconds of addr from the start up to the given time. Within a span the
+/// modifier is interpolated between its points, the last span decays towards the
+/// modifier projected for now.
+fn cumulative_at(
+    storage: &dyn Storage,
+    state: &State,
+    now: Timestamp,
+    addr: &Addr,
+    time: u64,
+) -> StdResult<Uint128> {
+    if let Some((since, point)) = latest_point(storage, addr, Some(time))? {
+        let (until, demurrage_amount) = match next_point(storage, addr, time)? {
+            Some((until, next)) => (until, next.demurrage_amount),
+            None => (now.seconds(), projected_demurrage_amount(state, now)),
+        };
+        let demurrage_amount = if until > since {
+            point.demurrage_amount
+                - (point.demurrage_amount.saturating_sub(demurrage_amount)) * u128::from(time - since)
+                    / u128::from(until - since)
+        } else {
+            point.demurrage_amount
+        };
+        return Ok(point.cumulative + held(&point, demurrage_amount, time - since));
+    }
+    let start = state.start_timestamp.seconds();
+    let elapsed = time.saturating_sub(start);
+    if elapsed == 0 {
+        return Ok(Uint128::zero());
+    }
+    // the balance held before the first point is what that point accumulated since the
+    // start, and an account without points still holds the balance it started with
+    let cumulative = match first_point(storage, addr)? {
+        Some((first, point)) => point.cumulative.multiply_ratio(elapsed, first - start),
+        None => {
+            let balance = balances().may_load(storage, addr)?.unwrap_or_default();
+            from_base_amount(balance, state.demurrage_amount) * Uint128::from(elapsed)
+        }
+    };
+    Ok(cumulative)
+}
+
+pub fn query_twab_balance(
+    deps: Deps,
+    env: Env,
+    address: String,
+    from: Timestamp,
+    to: Timestamp,
+) -> StdResult<TwabBalanceResponse> {
+    if from >= to || to > env.block.time {
+        return Err(StdError::generic_err("Window must be non-empty and not end in the future"));
+    }
+    let addr = deps.api.addr_validate(&address)?;
+    let state = STATE.load(deps.storage)?;
+    let (from, to) = (from.seconds(), to.seconds());
+
+    let now = env.block.time;
+    let total = cumulative_at(deps.storage, &state, now, &addr, to)?
+        - cumulative_at(deps.storage, &state, now, &addr, from)?;
+    Ok(TwabBalanceResponse {
+        balance: total / Uint128::from(to - from),
+    })
+}
@@ -0,0 +1,167 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Uint128};
+use cw4::Cw4Contract;
+
+use crate::contract::{apply_default_redistribution, change_period, prepare_balance_hooks};
+use crate::enumerable::page_limit;
+use crate::error::ContractError;
+use crate::freeze::assert_can_transfer;
+use crate::hook::BalanceDiff;
+use crate::query::UbiStatusResponse;
+use crate::state::{
+    balances, UbiRound, ADMIN, FROZEN, STATE, UBI_GROUP, UBI_PENDING, UBI_ROUND,
+};
+use crate::stats::{record_balance_changes, settle_tiered_tax};
+
+/// Keep the demurrage collected for the closed period for the next UBI round and
+/// return what is left for the sink, which is nothing when a group is configured.
+pub fn reserve_ubi(storage: &mut dyn Storage, distribution: u128) -> StdResult<u128> {
+    if UBI_GROUP.may_load(storage)?.is_none() {
+        return Ok(distribution);
+    }
+    let pending = UBI_PENDING.may_load(storage)?.unwrap_or_default();
+    UBI_PENDING.save(storage, &(pending + Uint128::from(distribution)))?;
+    Ok(0)
+}
+
+/// Set the cw4 group receiving the collected demurrage, None sends it to the sink again.
+/// Must be called by Admin
+pub fn execute_set_ubi_group(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    group: Option<String>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let mut res = Response::new();
+    let group_str = match group {
+        Some(group) => {
+            let addr = deps.api.addr_validate(&group)?;
+            UBI_GROUP.save(deps.storage, &Cw4Contract::new(addr))?;
+            group
+        }
+        None => {
+            // nothing is left behind for a group that is no longer paid
+            let pending = UBI_PENDING.may_load(deps.storage)?.unwrap_or_default();
+            let remaining = UBI_ROUND
+                .may_load(deps.storage)?
+                .map(|round| round.remaining)
+                .unwrap_or_default();
+            UBI_GROUP.remove(deps.storage);
+            UBI_PENDING.remove(deps.storage);
+            UBI_ROUND.remove(deps.storage);
+
+            let mut state = STATE.load(deps.storage)?;
+            let sink_res = apply_default_redistribution(
+                &mut deps,
+                &env.block,
+                &mut state,
+                (pending + remaining).u128(),
+            )?;
+            res = res.add_submessages(sink_res.messages);
+            "None".to_string()
+        }
+    };
+
+    Ok(res
+        .add_attribute("action", "set_ubi_group")
+        .add_attribute("group", group_str)
+        .add_attribute("sender", info.sender))
+}
+
+/// Pays the next members of the current round their weighted share of its pool.
+/// A new round starts with everything collected so far once the previous one ended,
+/// what is left when all members are paid goes to the sink. Anyone may call this.
+pub fn execute_distribute(
+    mut deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    assert_can_transfer(deps.storage, &[])?;
+    let group = UBI_GROUP
+        .may_load(deps.storage)?
+        .ok_or(ContractError::UbiDisabled {})?;
+    let limit = page_limit(limit);
+
+    let mut state = STATE.load(deps.storage)?;
+    let period_res = change_period(&mut deps, env.clone(), &mut state)?;
+
+    let mut round = match UBI_ROUND.may_load(deps.storage)? {
+        Some(round) => round,
+        None => {
+            let pool = UBI_PENDING.may_load(deps.storage)?.unwrap_or_default();
+            if pool.is_zero() {
+                return Err(ContractError::NothingToDistribute {});
+            }
+            UBI_PENDING.save(deps.storage, &Uint128::zero())?;
+            UbiRound {
+                pool,
+                remaining: pool,
+                total_weight: group.total_weight(&deps.querier)?,
+                cursor: None,
+            }
+        }
+    };
+
+    let members = group.list_members(&deps.querier, round.cursor.clone(), Some(limit as u32))?;
+    let mut settled = vec![];
+    let mut diffs = vec![];
+    let mut paid = Uint128::zero();
+    for member in &members {
+        round.cursor = Some(member.addr.clone());
+        let addr = Addr::unchecked(&member.addr);
+        if member.weight == 0 || round.total_weight == 0 || FROZEN.has(deps.storage, &addr) {
+            continue;
+        }
+        // weights may have grown since the round started, never pay out more than the pool
+        let share = round
+            .pool
+            .multiply_ratio(member.weight, round.total_weight)
+            .min(round.remaining);
+        if share.is_zero() {
+            continue;
+        }
+        round.remaining -= share;
+        paid += share;
+        settled.extend(settle_tiered_tax(deps.storage, &env.block, &[&addr])?);
+        let balance = balances().update(
+            deps.storage,
+            &addr,
+            |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + share) },
+        )?;
+        diffs.push(BalanceDiff::credited(&addr, balance, share));
+    }
+    record_balance_changes(deps.storage, &env.block, &diffs)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, diffs].concat())?;
+
+    let mut res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_attribute("action", "distribute")
+        .add_attribute("members", members.len().to_string())
+        .add_attribute("amount", paid);
+
+    if members.len() < limit {
+        // every member was paid, the rounding rest and frozen shares go to the sink
+        UBI_ROUND.remove(deps.storage);
+        let sink_res =
+            apply_default_redistribution(&mut deps, &env.block, &mut state, round.remaining.u128())?;
+        res = res
+            .add_submessages(sink_res.messages)
+            .add_attribute("round", "finished");
+    } else {
+        UBI_ROUND.save(deps.storage, &round)?;
+    }
+    Ok(res)
+}
+
+pub fn query_ubi_status(deps: Deps) -> StdResult<UbiStatusResponse> {
+    let group = UBI_GROUP
+        .may_load(deps.storage)?
+        .map(|group| group.addr().to_string());
+    Ok(UbiStatusResponse {
+        group,
+        pending: UBI_PENDING.may_load(deps.storage)?.unwrap_or_default(),
+        round: UBI_ROUND.may_load(deps.storage)?,
+    })
+}
@@ -0,0 +1,147 @@
+use cosmwasm_std::{
+    Addr, BlockInfo, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Storage,
+    Uint128,
+};
+use cw_utils::Scheduled;
+
+use crate::contract::{issue_supply, mint_tokens, prepare_balance_hooks};
+use crate::error::ContractError;
+use crate::freeze::assert_can_transfer;
+use crate::membership::assert_members;
+use crate::msg::VestingSchedule;
+use crate::query::VestingResponse;
+use crate::state::{Vesting, LAST_ACTIVITY, VESTING, balances};
+use crate::stats::settle_tiered_tax;
+
+/// Create a vesting of amount starting at the current block
+pub fn new_vesting(
+    block: &BlockInfo,
+    amount: Uint128,
+    schedule: VestingSchedule,
+) -> Result<Vesting, ContractError> {
+    let start = match schedule.end {
+        Scheduled::AtHeight(_) => Scheduled::AtHeight(block.height),
+        Scheduled::AtTime(_) => Scheduled::AtTime(block.time),
+    };
+    // comparing a height with a time is never true, so mixed schedules are rejected
+    let valid = schedule.cliff >= start && schedule.cliff <= schedule.end && schedule.end > start;
+    if !valid {
+        return Err(ContractError::InvalidVesting {});
+    }
+    Ok(Vesting {
+        amount,
+        start,
+        cliff: schedule.cliff,
+        end: schedule.end,
+    })
+}
+
+/// Amount of the vesting that is not released at the given block
+pub fn locked_amount(vesting: &Vesting, block: &BlockInfo) -> Uint128 {
+    if !vesting.cliff.is_triggered(block) {
+        return vesting.amount;
+    }
+    if vesting.end.is_triggered(block) {
+        return Uint128::zero();
+    }
+    let (start, end, now) = match (vesting.start, vesting.end) {
+        (Scheduled::AtHeight(start), Scheduled::AtHeight(end)) => (start, end, block.height),
+        (Scheduled::AtTime(start), Scheduled::AtTime(end)) => {
+            (start.seconds(), end.seconds(), block.time.seconds())
+        }
+        _ => return vesting.amount,
+    };
+    let released = vesting.amount.multiply_ratio(now - start, end - start);
+    vesting.amount - released
+}
+
+pub fn locked_balance(storage: &dyn Storage, block: &BlockInfo, addr: &Addr) -> StdResult<Uint128> {
+    let schedules = VESTING.may_load(storage, addr)?.unwrap_or_default();
+    Ok(schedules.iter().map(|v| locked_amount(v, block)).sum())
+}
+
+/// Lower the balance of the account by amount, the remaining balance must still
+/// cover the tokens locked by vesting. Records the block as the last activity of the
+/// account and drops the schedules that are fully released. Returns the new balance.
+pub fn debit_balance(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    addr: &Addr,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let schedules = VESTING.may_load(storage, addr)?.unwrap_or_default();
+    let locked = schedules.iter().map(|v| locked_amount(v, block)).sum();
+    let balance = balances().update(storage, addr, |balance| -> Result<_, ContractError> {
+        let balance = balance
+            .unwrap_or_default()
+            .checked_sub(amount)
+            .map_err(StdError::overflow)?;
+        if balance < locked {
+            return Err(ContractError::LockedTokens { locked });
+        }
+        Ok(balance)
+    })?;
+    LAST_ACTIVITY.save(storage, addr, &block.time)?;
+
+    let vesting: Vec<Vesting> = schedules
+        .iter()
+        .filter(|v| !locked_amount(v, block).is_zero())
+        .cloned()
+        .collect();
+    if vesting.is_empty() && !schedules.is_empty() {
+        VESTING.remove(storage, addr);
+    } else if vesting.len() < schedules.len() {
+        VESTING.save(storage, addr, &vesting)?;
+    }
+    Ok(balance)
+}
+
+pub fn add_vesting(storage: &mut dyn Storage, addr: &Addr, vesting: Vesting) -> StdResult<()> {
+    VESTING.update(storage, addr, |schedules| -> StdResult<_> {
+        let mut schedules = schedules.unwrap_or_default();
+        schedules.push(vesting);
+        Ok(schedules)
+    })?;
+    Ok(())
+}
+
+pub fn execute_mint_vesting(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128,
+    schedule: VestingSchedule,
+) -> Result<Response, ContractError> {
+    if amount == Uint128::zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    let rcpt_addr = deps.api.addr_validate(&recipient)?;
+    assert_can_transfer(deps.storage, &[&rcpt_addr])?;
+    assert_members(deps.as_ref(), &[&rcpt_addr])?;
+    let mut vesting = new_vesting(&env.block, amount, schedule)?;
+
+    let (state, period_res) = issue_supply(&mut deps, env.clone(), &info.sender, amount)?;
+
+    let settled = settle_tiered_tax(deps.storage, &env.block, &[&rcpt_addr])?;
+    let diff = mint_tokens(deps.storage, &env.block, &state, &rcpt_addr, amount)?;
+    // lock what was actually credited to the balance
+    vesting.amount = diff.new - diff.old;
+    add_vesting(deps.storage, &rcpt_addr, vesting)?;
+    let hooks = prepare_balance_hooks(deps.storage, [settled, vec![diff]].concat())?;
+
+    let res = Response::new()
+        .add_submessages(period_res.messages)
+        .add_submessages(hooks)
+        .add_attribute("action", "mint_vesting")
+        .add_attribute("to", recipient)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+pub fn query_vesting(deps: Deps, env: Env, address: String) -> StdResult<VestingResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let schedules = VESTING.may_load(deps.storage, &address)?.unwrap_or_default();
+    let locked = schedules.iter().map(|v| locked_amount(v, &env.block)).sum();
+    Ok(VestingResponse { schedules, locked })
+}